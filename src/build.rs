@@ -0,0 +1,344 @@
+use crate::{environment::Environment, extra::Extra, tool::Tool};
+
+use std::{
+    collections::HashMap,
+    env,
+    io::{self, Write},
+    path::Path,
+    process::Command,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Variable through which [`emit_build_info`] passes the metadata to [`build_info!`](crate::build_info)
+const BUILD_INFO_VAR: &str = crate::__build_info_var!();
+
+/// Toolchain and build metadata of the crate producing a report.
+/// Obtain an instance with [`build_info!`](crate::build_info), which captures the calling crate's
+/// package information and whatever [`emit_build_info`] exported from its build script.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInfo {
+    pub package_name: String,
+    pub package_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rustc_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// Resolved dependencies as `name@version`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub crate_versions: Vec<String>,
+}
+
+/// What the build script exports, see [`emit_build_info`]
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct Exported {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rustc_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    profile: Option<String>,
+    #[serde(default)]
+    features: Vec<String>,
+    #[serde(default)]
+    crate_versions: Vec<String>,
+}
+
+impl BuildInfo {
+    #[doc(hidden)]
+    pub fn __from_env(
+        package_name: &str,
+        package_version: &str,
+        debug_assertions: bool,
+        exported: Option<&str>,
+    ) -> Self {
+        // Written by `write_build_info`, so only unreadable if the versions of ctrf-rs differ
+        let exported: Exported = exported
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default();
+        let fallback_profile = if debug_assertions { "debug" } else { "release" };
+
+        Self {
+            package_name: String::from(package_name),
+            package_version: String::from(package_version),
+            rustc_version: exported.rustc_version,
+            target: exported.target,
+            profile: Some(
+                exported
+                    .profile
+                    .unwrap_or_else(|| String::from(fallback_profile)),
+            ),
+            features: exported.features,
+            crate_versions: exported.crate_versions,
+        }
+    }
+
+    /// Creates a Tool whose version is the calling crate's package version
+    pub fn tool(&self, name: &str) -> Tool {
+        let mut tool = Tool::new(name, Some(self.package_version.clone()));
+        if let Some(rustc) = &self.rustc_version {
+            tool.insert_extra(String::from("rustcVersion"), Value::from(rustc.as_str()));
+        }
+
+        tool
+    }
+
+    /// Records the build metadata in the Environment's `extra`.
+    /// `app_name` and `app_version` are filled from the package when not already set.
+    pub fn record(&self, environment: &mut Environment) {
+        environment
+            .app_name
+            .get_or_insert_with(|| self.package_name.clone());
+        environment
+            .app_version
+            .get_or_insert_with(|| self.package_version.clone());
+
+        if let Value::Object(map) = serde_json::json!({
+            "rustcVersion": self.rustc_version,
+            "target": self.target,
+            "profile": self.profile,
+            "features": self.features,
+            "crateVersions": self.crate_versions,
+        }) {
+            for (key, value) in map {
+                if !value.is_null() {
+                    environment.insert_extra(key, value);
+                }
+            }
+        }
+    }
+}
+
+/// Captures the calling crate's [`BuildInfo`](crate::build::BuildInfo).
+/// Toolchain fields are only populated when the crate's build script calls
+/// [`emit_build_info`](crate::build::emit_build_info).
+#[macro_export]
+macro_rules! build_info {
+    () => {
+        $crate::build::BuildInfo::__from_env(
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            cfg!(debug_assertions),
+            option_env!($crate::__build_info_var!()),
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __build_info_var {
+    () => {
+        "CTRF_BUILD_INFO"
+    };
+}
+
+/// Exports toolchain and build metadata to the crate being compiled.
+/// Call this from a build script (`build.rs`) so [`build_info!`](crate::build_info) can pick it up.
+///
+/// No `rerun-if-changed` is emitted, so Cargo keeps its default of re-running the build script
+/// whenever a file of the package changes.  If the build script emits `rerun-if-changed` lines of
+/// its own, Cargo only re-runs it for those paths; list `Cargo.lock` among them to keep the crate
+/// versions current.
+pub fn emit_build_info() -> io::Result<()> {
+    write_build_info(io::stdout().lock(), &env::vars().collect())
+}
+
+/// Writes the build script output, given the build script's environment variables
+fn write_build_info(mut out: impl Write, vars: &HashMap<String, String>) -> io::Result<()> {
+    let rustc = vars.get("RUSTC").map_or("rustc", String::as_str);
+    let output = Command::new(rustc).arg("--version").output()?;
+    let rustc_version = String::from(String::from_utf8_lossy(&output.stdout).trim());
+
+    let mut features = features(vars.keys().cloned());
+    features.sort();
+
+    let manifest_dir = vars.get("CARGO_MANIFEST_DIR").cloned().unwrap_or_default();
+    // Workspace members share the lock file of the workspace root
+    let lock = Path::new(&manifest_dir)
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|lock| lock.is_file());
+    let crate_versions = match lock {
+        Some(lock) => crate_versions(&std::fs::read_to_string(lock)?),
+        None => vec![],
+    };
+
+    let exported = Exported {
+        rustc_version: Some(rustc_version).filter(|v| !v.is_empty()),
+        target: vars.get("TARGET").cloned(),
+        profile: vars.get("PROFILE").cloned(),
+        features,
+        crate_versions,
+    };
+    writeln!(
+        out,
+        "cargo:rustc-env={BUILD_INFO_VAR}={}",
+        serde_json::to_string(&exported)?
+    )
+}
+
+/// Recovers enabled feature names from `CARGO_FEATURE_*` variables.
+/// Cargo upper-cases names and replaces `-` with `_`, so the result is a lower-cased approximation.
+fn features(vars: impl Iterator<Item = String>) -> Vec<String> {
+    vars.filter_map(|k| {
+        k.strip_prefix("CARGO_FEATURE_")
+            .map(|f| f.to_ascii_lowercase())
+    })
+    .collect()
+}
+
+/// Lists the `[[package]]` entries of a `Cargo.lock` as `name@version`
+fn crate_versions(lock: &str) -> Vec<String> {
+    let mut versions = vec![];
+    let mut name = None;
+
+    for line in lock.lines().map(str::trim) {
+        if line == "[[package]]" {
+            name = None;
+        } else if let Some(value) = line.strip_prefix("name = ") {
+            name = Some(value.trim_matches('"'));
+        } else if let Some(value) = line.strip_prefix("version = ") {
+            if let Some(name) = name.take() {
+                versions.push(format!("{name}@{}", value.trim_matches('"')));
+            }
+        }
+    }
+
+    versions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_package_info() {
+        let info = crate::build_info!();
+
+        assert_eq!(info.package_name, "ctrf-rs");
+        assert_eq!(info.package_version, env!("CARGO_PKG_VERSION"));
+        assert!(info.profile.is_some());
+
+        let tool = info.tool("ctrf-rs");
+        assert_eq!(
+            serde_json::to_value(tool).unwrap()["version"],
+            env!("CARGO_PKG_VERSION")
+        );
+    }
+
+    #[test]
+    fn record_into_environment() {
+        let info = BuildInfo::__from_env(
+            "app",
+            "1.2.3",
+            false,
+            Some(
+                r#"{"rustcVersion":"rustc 1.83.0","target":"x86_64-unknown-linux-gnu",
+                "features":["default","std"],"crateVersions":["serde@1.0.0","uuid@1.16.0"]}"#,
+            ),
+        );
+        let mut environment = Environment::new();
+
+        info.record(&mut environment);
+
+        assert_eq!(environment.app_name.as_deref(), Some("app"));
+        assert_eq!(environment.app_version.as_deref(), Some("1.2.3"));
+        assert_eq!(environment.extra["rustcVersion"], "rustc 1.83.0");
+        assert_eq!(environment.extra["target"], "x86_64-unknown-linux-gnu");
+        assert_eq!(environment.extra["profile"], "release");
+        assert_eq!(
+            environment.extra["features"],
+            serde_json::json!(["default", "std"])
+        );
+        assert_eq!(
+            environment.extra["crateVersions"],
+            serde_json::json!(["serde@1.0.0", "uuid@1.16.0"])
+        );
+    }
+
+    #[test]
+    fn write_build_script_output() -> io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("ctrf-rs-build-{}", uuid::Uuid::new_v4()));
+        let manifest_dir = dir.join("member");
+        std::fs::create_dir_all(&manifest_dir)?;
+        std::fs::write(
+            dir.join("Cargo.lock"),
+            "[[package]]\nname = \"app\"\nversion = \"1.2.3\"\n",
+        )?;
+        let vars: HashMap<String, String> = [
+            (
+                "CARGO_MANIFEST_DIR",
+                manifest_dir.to_string_lossy().as_ref(),
+            ),
+            ("TARGET", "x86_64-unknown-linux-gnu"),
+            ("PROFILE", "release"),
+            ("CARGO_FEATURE_STD", "1"),
+        ]
+        .iter()
+        .map(|(k, v)| (String::from(*k), String::from(*v)))
+        .collect();
+
+        let mut out = vec![];
+        write_build_info(&mut out, &vars)?;
+        std::fs::remove_dir_all(dir)?;
+
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), 1);
+        assert!(!out.contains("rerun-if-changed"));
+        let exported = out
+            .trim_end()
+            .strip_prefix(&format!("cargo:rustc-env={BUILD_INFO_VAR}="))
+            .unwrap();
+        let info = BuildInfo::__from_env("app", "1.2.3", true, Some(exported));
+        assert!(info.rustc_version.unwrap().starts_with("rustc "));
+        assert_eq!(info.target.as_deref(), Some("x86_64-unknown-linux-gnu"));
+        assert_eq!(info.profile.as_deref(), Some("release"));
+        assert_eq!(info.features, ["std"]);
+        assert_eq!(info.crate_versions, ["app@1.2.3"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_lock_file() {
+        let lock = r#"
+# This file is automatically @generated by Cargo.
+version = 3
+
+[[package]]
+name = "ctrf-rs"
+version = "0.1.0"
+dependencies = [
+ "semver",
+]
+
+[[package]]
+name = "semver"
+version = "1.0.26"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+
+        assert_eq!(crate_versions(lock), vec!["ctrf-rs@0.1.0", "semver@1.0.26"]);
+    }
+
+    #[test]
+    fn parse_features() {
+        let vars = [
+            "CARGO_FEATURE_DEFAULT",
+            "CARGO_PKG_NAME",
+            "CARGO_FEATURE_SERDE_JSON",
+        ];
+
+        assert_eq!(
+            features(vars.iter().map(|v| String::from(*v))),
+            vec!["default", "serde_json"]
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{error::Result, report::Report};
+
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::{Component, Path, PathBuf},
+};
+
+/// Name of the report document within a bundle
+pub const REPORT_ENTRY: &str = "report.json";
+/// Directory within a bundle holding the attachment and screenshot files
+pub const ATTACHMENTS_DIR: &str = "attachments";
+
+impl Report {
+    /// Writes the report together with every referenced attachment and screenshot file to a tar
+    /// archive.  Within the archive, references point to the bundled copies.
+    /// Only tar is supported; compress the writer to produce a `.tar.gz` or `.tar.zst`.
+    /// Relative paths are resolved against `base_dir`; inline attachments are left as they are.
+    pub fn to_bundle(&self, writer: impl Write, base_dir: impl AsRef<Path>) -> Result<()> {
+        let base_dir = base_dir.as_ref();
+        let mut report = self.clone();
+        let mut archive = tar::Builder::new(writer);
+        let mut bundled: HashMap<PathBuf, PathBuf> = HashMap::new();
+
+        let mut bundle_file = |path: &Path| -> io::Result<PathBuf> {
+            let source = base_dir.join(path);
+            if let Some(entry) = bundled.get(&source) {
+                return Ok(entry.clone());
+            }
+
+            let file_name = source.file_name().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("attachment path '{}' has no file name", path.display()),
+                )
+            })?;
+            // Prefix with a counter so equally named files from different directories don't clash
+            let entry = Path::new(ATTACHMENTS_DIR).join(format!(
+                "{}-{}",
+                bundled.len(),
+                file_name.to_string_lossy()
+            ));
+
+            archive.append_file(&entry, &mut File::open(&source)?)?;
+            bundled.insert(source, entry.clone());
+
+            Ok(entry)
+        };
+
+        for test in report.results_mut().tests_mut() {
+            for attachment in &mut test.attachments {
+                if !attachment.is_inline() {
+                    attachment.path = bundle_file(&attachment.path)?;
+                }
+            }
+
+            if let Some(screenshot) = &mut test.screenshot {
+                if base_dir.join(&screenshot).is_file() {
+                    let entry = bundle_file(Path::new(screenshot))?;
+                    *screenshot = entry.to_string_lossy().into_owned();
+                }
+            }
+        }
+
+        let json = report.to_vec_pretty()?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(json.len() as u64);
+        header.set_mode(0o644);
+        archive.append_data(&mut header, REPORT_ENTRY, json.as_slice())?;
+
+        archive.into_inner()?.flush()?;
+
+        Ok(())
+    }
+
+    /// Unpacks a bundle written by [`Report::to_bundle`] into `dest` and reads the contained report.
+    /// Attachment and screenshot references are rewritten to the unpacked files.
+    /// Fails without unpacking further if an entry or reference would escape `dest`.
+    pub fn from_bundle(reader: impl Read, dest: impl AsRef<Path>) -> Result<Self> {
+        let dest = dest.as_ref();
+        fs::create_dir_all(dest)?;
+
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            contained(&entry.path()?)?;
+            entry.unpack_in(dest)?;
+        }
+
+        let mut report = Report::from_reader(File::open(dest.join(REPORT_ENTRY))?)?;
+
+        for test in report.results_mut().tests_mut() {
+            for attachment in &mut test.attachments {
+                if attachment.path.starts_with(ATTACHMENTS_DIR) {
+                    contained(&attachment.path)?;
+                    attachment.path = dest.join(&attachment.path);
+                }
+            }
+
+            if let Some(screenshot) = &mut test.screenshot {
+                if Path::new(screenshot).starts_with(ATTACHMENTS_DIR) {
+                    contained(Path::new(screenshot))?;
+                    *screenshot = dest.join(&screenshot).to_string_lossy().into_owned();
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Rejects paths that are absolute or contain `..`, as they could point outside the bundle
+fn contained(path: &Path) -> io::Result<()> {
+    if path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("bundle path '{}' leaves the bundle", path.display()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::Error,
+        report::Report,
+        results::ResultsBuilder,
+        test::{attachment::Attachment, Status, Test},
+        tool::Tool,
+    };
+
+    use std::{collections::HashMap, fs, path::PathBuf, time::Duration, time::SystemTime};
+
+    use uuid::Uuid;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ctrf-rs-bundle-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn report_with_attachment(path: &str) -> Report {
+        let mut test = Test::new(String::from("t"), Status::Failed, Duration::from_millis(1));
+        test.attachments.push(Attachment {
+            name: String::from("log"),
+            content_type: String::from("text/plain"),
+            path: PathBuf::from(path),
+            extra: HashMap::new(),
+        });
+        test.screenshot = Some(String::from("shot.png"));
+
+        let mut builder = ResultsBuilder::new(Tool::new("ctrf-rs", None));
+        builder.add_test(test);
+        let time = SystemTime::now();
+        Report::new(None, None, None, builder.build(time, time))
+    }
+
+    #[test]
+    fn bundle_round_trip() {
+        let source = temp_dir();
+        fs::create_dir_all(source.join("logs")).unwrap();
+        fs::write(source.join("logs/out.txt"), "log contents").unwrap();
+        fs::write(source.join("shot.png"), [0x89, b'P', b'N', b'G']).unwrap();
+
+        let report = report_with_attachment("logs/out.txt");
+        let mut bundle = vec![];
+        report.to_bundle(&mut bundle, &source).unwrap();
+
+        let dest = temp_dir();
+        let reopened = Report::from_bundle(bundle.as_slice(), &dest).unwrap();
+        let test = &reopened.results().tests()[0];
+
+        assert!(test.attachments[0].path.starts_with(&dest));
+        assert_eq!(
+            test.attachments[0].data(&dest).unwrap(),
+            b"log contents".to_vec()
+        );
+        assert_eq!(
+            fs::read(test.screenshot.as_ref().unwrap()).unwrap(),
+            vec![0x89, b'P', b'N', b'G']
+        );
+
+        fs::remove_dir_all(source).unwrap();
+        fs::remove_dir_all(dest).unwrap();
+    }
+
+    #[test]
+    fn bundle_missing_attachment() {
+        let source = temp_dir();
+        let report = report_with_attachment("missing.txt");
+
+        let result = report.to_bundle(vec![], &source);
+
+        match result {
+            Err(Error::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+            other => panic!("expected an I/O error, got {:?}", other),
+        }
+
+        fs::remove_dir_all(source).unwrap();
+    }
+
+    /// A tar archive with a single entry, named without the checks of `tar::Builder`
+    fn archive(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut header = tar::Header::new_gnu();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+
+        let mut archive = tar::Builder::new(vec![]);
+        archive.append(&header, data).unwrap();
+        archive.into_inner().unwrap()
+    }
+
+    #[test]
+    fn bundle_parent_dir() {
+        let dest = temp_dir();
+        let nested = dest.join("nested");
+
+        let bundle = archive("../escaped.txt", b"escaped");
+        match Report::from_bundle(bundle.as_slice(), &nested) {
+            Err(Error::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidData),
+            other => panic!("expected an I/O error, got {:?}", other),
+        }
+        assert!(!dest.join("escaped.txt").exists());
+
+        let json = report_with_attachment("attachments/../../escaped.txt")
+            .to_vec_pretty()
+            .unwrap();
+        let bundle = archive(super::REPORT_ENTRY, &json);
+        match Report::from_bundle(bundle.as_slice(), &nested) {
+            Err(Error::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidData),
+            other => panic!("expected an I/O error, got {:?}", other),
+        }
+
+        fs::remove_dir_all(dest).unwrap();
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{error::Result, report::Report};
+
+use std::io::Write;
+
+use serde_json::{Map, Value};
+
+/// Options of the canonical serialization, see [`Report::to_string_canonical`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CanonicalOptions {
+    /// Order tests by suite, then name, then their canonical serialization
+    pub sort_tests: bool,
+    /// Write test filepaths and attachment paths with `/` separators and without a leading `./`
+    pub normalize_paths: bool,
+    /// Drop `timestamp` and the test `start`/`stop` times, and zero the summary `start`/`stop`
+    /// and the test durations
+    pub strip_timing: bool,
+}
+
+impl Default for CanonicalOptions {
+    /// Sorts tests and normalizes paths, but keeps timing
+    fn default() -> Self {
+        Self {
+            sort_tests: true,
+            normalize_paths: true,
+            strip_timing: false,
+        }
+    }
+}
+
+impl Report {
+    /// Outputs the report as a String of canonical JSON: object keys are sorted at every level and
+    /// the report is normalized according to `options`, so equal reports serialize identically.
+    pub fn to_string_canonical(&self, options: &CanonicalOptions) -> Result<String> {
+        Ok(serde_json::to_string(&self.to_canonical_value(options)?)?)
+    }
+
+    /// Outputs the report as a pretty-printed String of canonical JSON
+    pub fn to_string_canonical_pretty(&self, options: &CanonicalOptions) -> Result<String> {
+        Ok(serde_json::to_string_pretty(
+            &self.to_canonical_value(options)?,
+        )?)
+    }
+
+    /// Outputs the report as a canonical JSON byte vector
+    pub fn to_vec_canonical(&self, options: &CanonicalOptions) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(&self.to_canonical_value(options)?)?)
+    }
+
+    /// Outputs the report as canonical JSON to the provided I/O stream
+    pub fn to_writer_canonical(
+        &self,
+        writer: impl Write,
+        options: &CanonicalOptions,
+    ) -> Result<()> {
+        Ok(serde_json::to_writer(
+            writer,
+            &self.to_canonical_value(options)?,
+        )?)
+    }
+
+    fn to_canonical_value(&self, options: &CanonicalOptions) -> Result<Value> {
+        let mut value = serde_json::to_value(self)?;
+        let root = value
+            .as_object_mut()
+            .expect("a report serializes to an object");
+
+        if options.strip_timing {
+            root.remove("timestamp");
+        }
+        if let Some(results) = root.get_mut("results").and_then(Value::as_object_mut) {
+            if let Some(summary) = results.get_mut("summary").and_then(Value::as_object_mut) {
+                if options.strip_timing {
+                    summary.insert(String::from("start"), Value::from(0));
+                    summary.insert(String::from("stop"), Value::from(0));
+                }
+            }
+            if let Some(tests) = results.get_mut("tests").and_then(Value::as_array_mut) {
+                for test in tests.iter_mut().filter_map(Value::as_object_mut) {
+                    canonicalize_test(test, options);
+                }
+                if options.sort_tests {
+                    tests.sort_by_cached_key(test_order);
+                }
+            }
+        }
+
+        Ok(sort_keys(value))
+    }
+}
+
+fn canonicalize_test(test: &mut Map<String, Value>, options: &CanonicalOptions) {
+    if options.strip_timing {
+        test.remove("start");
+        test.remove("stop");
+        test.insert(String::from("duration"), Value::from(0));
+    }
+
+    if options.normalize_paths {
+        if let Some(Value::String(path)) = test.get_mut("filepath") {
+            *path = normalize_path(path);
+        }
+        let attachments = test.get_mut("attachments").and_then(Value::as_array_mut);
+        for attachment in attachments.into_iter().flatten() {
+            if let Some(Value::String(path)) = attachment.get_mut("path") {
+                *path = normalize_path(path);
+            }
+        }
+    }
+}
+
+/// Orders by suite and name, breaking ties with the sorted-key serialization so that the input
+/// order of otherwise equally named tests does not show
+fn test_order(test: &Value) -> (String, String, String) {
+    let field = |name: &str| {
+        test.get(name)
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_owned()
+    };
+
+    (
+        field("suite"),
+        field("name"),
+        sort_keys(test.clone()).to_string(),
+    )
+}
+
+/// Uses `/` separators and drops any leading `./`
+pub(crate) fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut path = path.as_str();
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest;
+    }
+
+    String::from(path)
+}
+
+/// Rebuilds a JSON value with object keys in sorted order at every level.
+/// Inserting in order sorts the output whether or not `serde_json` preserves insertion order.
+pub(crate) fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_keys(value)))
+                    .collect(),
+            )
+        }
+        scalar => scalar,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        extra::Extra,
+        results::ResultsBuilder,
+        test::{Status, Test},
+        tool::Tool,
+    };
+
+    use std::{
+        path::PathBuf,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    use serde_json::json;
+
+    fn report(order: &[usize], offset: u64) -> Report {
+        let mut tests = vec![];
+        for (t, suite) in ["b", "a", "a"].iter().enumerate() {
+            let mut test = Test::new(
+                format!("test{t}"),
+                Status::Passed,
+                Duration::from_millis(offset + t as u64),
+            );
+            test.suite = Some(String::from(*suite));
+            test.filepath = Some(PathBuf::from(format!(".\\tests\\{suite}.rs")));
+            for key in ["z", "m", "a"] {
+                test.parameters
+                    .insert(String::from(key), json!({"y": 1, "x": 2}));
+            }
+            tests.push(test);
+        }
+
+        let mut builder = ResultsBuilder::new(Tool::new("ctrf-rs", None));
+        for &t in order {
+            builder.add_test(tests[t].clone());
+        }
+        builder
+            .set_extra("k", &json!({"b": [1], "a": null}))
+            .unwrap();
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000 + offset);
+        Report::new(None, Some(start), None, builder.build(start, start))
+    }
+
+    #[test]
+    fn canonical_output_is_reproducible() -> Result<()> {
+        let options = CanonicalOptions::default();
+        let a = report(&[0, 1, 2], 0);
+        let b = report(&[2, 0, 1], 0);
+
+        let canonical = a.to_string_canonical(&options)?;
+        assert_eq!(canonical, b.to_string_canonical(&options)?);
+        assert!(canonical.contains(r#""parameters":{"a":{"x":2,"y":1},"m""#));
+        assert!(canonical.contains(r#""filepath":"tests/a.rs""#));
+        assert!(canonical.find("test1").unwrap() < canonical.find("test2").unwrap());
+        assert!(canonical.find("test2").unwrap() < canonical.find("test0").unwrap());
+
+        Ok(())
+    }
+
+    #[test]
+    fn ties_are_ordered_by_contents() -> Result<()> {
+        let options = CanonicalOptions::default();
+        let test = |status| Test::new(String::from("t"), status, Duration::from_millis(1));
+        let report = |statuses: &[Status]| {
+            let mut builder = ResultsBuilder::new(Tool::new("ctrf-rs", None));
+            for status in statuses {
+                builder.add_test(test(*status));
+            }
+            let start = UNIX_EPOCH;
+            Report::new(None, None, None, builder.build(start, start))
+        };
+
+        let a = report(&[Status::Passed, Status::Failed]).to_string_canonical(&options)?;
+        let b = report(&[Status::Failed, Status::Passed]).to_string_canonical(&options)?;
+
+        assert_eq!(a, b);
+        assert!(a.find(r#""status":"failed""#).unwrap() < a.find(r#""status":"passed""#).unwrap());
+
+        Ok(())
+    }
+
+    #[test]
+    fn strip_timing() -> Result<()> {
+        let options = CanonicalOptions {
+            strip_timing: true,
+            ..CanonicalOptions::default()
+        };
+        let a = report(&[0, 1, 2], 0).to_vec_canonical(&options)?;
+        let b = report(&[0, 1, 2], 60).to_vec_canonical(&options)?;
+
+        assert_eq!(a, b);
+        assert!(Report::from_slice(&a).is_ok());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+use crate::error::Result;
+
+use std::{
+    borrow::Cow,
+    io::{self, Read},
+};
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+use std::io::Write;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression formats for writing reports, each enabled by the cargo feature of the same name.
+/// Reading detects the format from its magic bytes.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Compression {
+    #[cfg(feature = "gzip")]
+    Gzip,
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+impl Compression {
+    /// Compresses whatever `write` writes to `writer`, at the format's default level
+    pub(crate) fn compress(
+        self,
+        writer: impl Write,
+        write: impl FnOnce(&mut dyn Write) -> Result<()>,
+    ) -> Result<()> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Self::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+                write(&mut encoder)?;
+                encoder.finish()?.flush()?;
+            }
+            #[cfg(feature = "zstd")]
+            Self::Zstd => {
+                let mut encoder = zstd::Encoder::new(writer, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+                write(&mut encoder)?;
+                encoder.finish()?.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Decompresses `data` if it starts with the magic bytes of a known format, otherwise returns it as is.
+/// Fails if the format is recognized but its feature is not enabled.
+pub(crate) fn decompress(data: &[u8]) -> Result<Cow<'_, [u8]>> {
+    if data.starts_with(&GZIP_MAGIC) {
+        #[cfg(feature = "gzip")]
+        return decode(flate2::read::MultiGzDecoder::new(data));
+        #[cfg(not(feature = "gzip"))]
+        return Err(disabled("gzip"));
+    }
+
+    if data.starts_with(&ZSTD_MAGIC) {
+        #[cfg(feature = "zstd")]
+        return decode(zstd::Decoder::new(data)?);
+        #[cfg(not(feature = "zstd"))]
+        return Err(disabled("zstd"));
+    }
+
+    Ok(Cow::Borrowed(data))
+}
+
+/// Like [`decompress`], but decompresses a stream as it is read
+pub(crate) fn decompress_reader<'a>(mut r: impl Read + 'a) -> Result<Box<dyn Read + 'a>> {
+    let mut magic = [0; 4];
+    let mut len = 0;
+    while len < magic.len() {
+        match r.read(&mut magic[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    let magic = &magic[..len];
+    let r = io::Cursor::new(magic.to_vec()).chain(r);
+
+    if magic.starts_with(&GZIP_MAGIC) {
+        #[cfg(feature = "gzip")]
+        return Ok(Box::new(flate2::read::MultiGzDecoder::new(r)));
+        #[cfg(not(feature = "gzip"))]
+        return Err(disabled("gzip"));
+    }
+
+    if magic.starts_with(&ZSTD_MAGIC) {
+        #[cfg(feature = "zstd")]
+        return Ok(Box::new(zstd::Decoder::new(r)?));
+        #[cfg(not(feature = "zstd"))]
+        return Err(disabled("zstd"));
+    }
+
+    Ok(Box::new(r))
+}
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+fn decode(mut decoder: impl Read) -> Result<Cow<'static, [u8]>> {
+    let mut buf = vec![];
+    decoder.read_to_end(&mut buf)?;
+
+    Ok(Cow::Owned(buf))
+}
+
+#[cfg(not(all(feature = "gzip", feature = "zstd")))]
+fn disabled(feature: &str) -> crate::error::Error {
+    crate::error::Error::Io(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("report is {feature}-compressed, but the '{feature}' feature is not enabled"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{report::Report, results::ResultsBuilder, tool::Tool};
+
+    use std::time::SystemTime;
+
+    fn report() -> Report {
+        let time = SystemTime::now();
+        Report::new(
+            None,
+            None,
+            None,
+            ResultsBuilder::new(Tool::new("ctrf-rs", None)).build(time, time),
+        )
+    }
+
+    #[test]
+    fn plain_json_is_untouched() -> Result<()> {
+        let json = report().to_vec()?;
+
+        assert!(matches!(decompress(&json)?, Cow::Borrowed(_)));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_round_trip() -> Result<()> {
+        let report = report();
+        let compressed = report.to_vec_compressed(Compression::Gzip)?;
+
+        assert!(compressed.starts_with(&GZIP_MAGIC));
+        assert_eq!(Report::from_reader(compressed.as_slice())?, report);
+
+        let pretty = report.to_vec_pretty_compressed(Compression::Gzip)?;
+        let mut decompressed = vec![];
+        flate2::read::GzDecoder::new(pretty.as_slice()).read_to_end(&mut decompressed)?;
+        assert_eq!(decompressed, report.to_vec_pretty()?);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_round_trip() -> Result<()> {
+        let report = report();
+        let compressed = report.to_vec_compressed(Compression::Zstd)?;
+
+        assert!(compressed.starts_with(&ZSTD_MAGIC));
+        assert_eq!(Report::from_slice(&compressed)?, report);
+
+        let mut pretty = vec![];
+        report.to_writer_pretty_compressed(&mut pretty, Compression::Zstd)?;
+        assert_eq!(
+            zstd::decode_all(pretty.as_slice())?,
+            report.to_vec_pretty()?
+        );
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    #[test]
+    fn disabled_format() {
+        let result = Report::from_slice(&[0x28, 0xb5, 0x2f, 0xfd, 0x00]);
+
+        assert!(matches!(result, Err(crate::error::Error::Io(_))));
+    }
+}
@@ -0,0 +1,4 @@
+//! Converters from the output of other test and benchmark tools into CTRF Tests.
+
+pub mod criterion;
+pub mod mutants;
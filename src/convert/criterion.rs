@@ -0,0 +1,337 @@
+use crate::{
+    error::{Error, Result},
+    extra::{Extra, ExtraExtension},
+    test::{Status, Test},
+};
+
+use std::{fs, io, path::Path, time::Duration};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+/// `test_type` of the converted Tests
+pub const BENCHMARK_TEST_TYPE: &str = "benchmark";
+
+/// Options of the criterion conversion
+#[derive(Clone, Debug, PartialEq)]
+pub struct CriterionOptions {
+    /// Relative increase of the mean time over the saved baseline above which a benchmark fails,
+    /// e.g. `0.05` for 5%.  The lower bound of the change's confidence interval is compared, so
+    /// noise within the interval does not fail a benchmark.
+    pub regression_threshold: f64,
+}
+
+impl Default for CriterionOptions {
+    /// Fails benchmarks that became more than 5% slower
+    fn default() -> Self {
+        Self {
+            regression_threshold: 0.05,
+        }
+    }
+}
+
+/// Measurements of a benchmark, recorded in the converted Test's `extra`.
+/// Times are in nanoseconds per iteration.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkMeasurement {
+    pub mean: f64,
+    /// Lower bound of the confidence interval of the mean
+    pub mean_lower: f64,
+    /// Upper bound of the confidence interval of the mean
+    pub mean_upper: f64,
+    pub median: f64,
+    pub std_dev: f64,
+    /// Absent for flat sampling
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slope: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub throughput: Option<Value>,
+    /// Absent without a saved baseline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change: Option<BenchmarkChange>,
+}
+
+impl ExtraExtension for BenchmarkMeasurement {
+    const KEY: &'static str = "benchmark";
+}
+
+/// Change of a benchmark since the saved baseline, as relative differences: `0.1` is 10% slower
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkChange {
+    pub mean: f64,
+    pub mean_lower: f64,
+    pub mean_upper: f64,
+    pub median: f64,
+}
+
+#[derive(Deserialize)]
+struct BenchmarkId {
+    group_id: String,
+    full_id: String,
+    #[serde(default)]
+    throughput: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct Estimates {
+    mean: Estimate,
+    median: Estimate,
+    std_dev: Estimate,
+    #[serde(default)]
+    slope: Option<Estimate>,
+}
+
+#[derive(Deserialize)]
+struct ChangeEstimates {
+    mean: Estimate,
+    median: Estimate,
+}
+
+#[derive(Deserialize)]
+struct Estimate {
+    point_estimate: f64,
+    confidence_interval: ConfidenceInterval,
+}
+
+#[derive(Deserialize)]
+struct ConfidenceInterval {
+    lower_bound: f64,
+    upper_bound: f64,
+}
+
+/// Converts every benchmark found below a criterion output directory, usually
+/// `target/criterion`, ordered by path
+pub fn read_dir(dir: impl AsRef<Path>, options: &CriterionOptions) -> Result<Vec<Test>> {
+    let mut tests = vec![];
+    visit(dir.as_ref(), options, &mut tests)?;
+
+    Ok(tests)
+}
+
+/// Converts the benchmark whose results are in `dir`, the directory holding its `new`
+/// measurements and `change` since the baseline.  Returns None if there are no measurements.
+pub fn read_benchmark(dir: impl AsRef<Path>, options: &CriterionOptions) -> Result<Option<Test>> {
+    let dir = dir.as_ref();
+    let id: BenchmarkId = match read_json(&dir.join("new/benchmark.json"))? {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+    let estimates: Estimates = match read_json(&dir.join("new/estimates.json"))? {
+        Some(estimates) => estimates,
+        None => return Ok(None),
+    };
+    let change: Option<ChangeEstimates> = read_json(&dir.join("change/estimates.json"))?;
+
+    let mean = estimates.mean.point_estimate;
+    let mut test = Test::new(
+        id.full_id,
+        Status::Passed,
+        Duration::from_nanos(mean.max(0.0).round() as u64),
+    );
+    test.suite = Some(id.group_id);
+    test.test_type = Some(String::from(BENCHMARK_TEST_TYPE));
+
+    if let Some(change) = &change {
+        let regression = change.mean.confidence_interval.lower_bound;
+        if regression > options.regression_threshold {
+            test.fail_test();
+            test.message = Some(format!(
+                "mean time regressed by {:.2}% (at least {:.2}%), above the threshold of {:.2}%",
+                change.mean.point_estimate * 100.0,
+                regression * 100.0,
+                options.regression_threshold * 100.0
+            ));
+        }
+    }
+
+    test.set_extension(&BenchmarkMeasurement {
+        mean,
+        mean_lower: estimates.mean.confidence_interval.lower_bound,
+        mean_upper: estimates.mean.confidence_interval.upper_bound,
+        median: estimates.median.point_estimate,
+        std_dev: estimates.std_dev.point_estimate,
+        slope: estimates.slope.map(|slope| slope.point_estimate),
+        throughput: id.throughput,
+        change: change.map(|change| BenchmarkChange {
+            mean: change.mean.point_estimate,
+            mean_lower: change.mean.confidence_interval.lower_bound,
+            mean_upper: change.mean.confidence_interval.upper_bound,
+            median: change.median.point_estimate,
+        }),
+    })?;
+
+    Ok(Some(test))
+}
+
+fn visit(dir: &Path, options: &CriterionOptions, tests: &mut Vec<Test>) -> Result<()> {
+    if let Some(test) = read_benchmark(dir, options)? {
+        tests.push(test);
+        return Ok(());
+    }
+
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for path in entries {
+        // HTML reports are not measurements
+        if path.is_dir() && !path.ends_with("report") {
+            visit(&path, options, tests)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads a JSON file, returning None if it does not exist
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut deserializer = serde_json::Deserializer::from_slice(&bytes);
+
+    serde_path_to_error::deserialize(&mut deserializer)
+        .map(Some)
+        .map_err(|e| Error::tracked("$", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn estimate(point: f64) -> Value {
+        json!({
+            "confidence_interval": {
+                "confidence_level": 0.95,
+                "lower_bound": point * 0.9,
+                "upper_bound": point * 1.1
+            },
+            "point_estimate": point,
+            "standard_error": point * 0.01
+        })
+    }
+
+    fn write(dir: &Path, file: &str, value: Value) {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, value.to_string()).unwrap();
+    }
+
+    fn bench(root: &Path, group: &str, function: &str, mean: f64, change: Option<f64>) -> PathBuf {
+        let dir = root.join(group).join(function);
+        write(
+            &dir,
+            "new/benchmark.json",
+            json!({
+                "group_id": group,
+                "function_id": function,
+                "value_str": null,
+                "throughput": null,
+                "full_id": format!("{group}/{function}"),
+                "directory_name": format!("{group}/{function}"),
+                "title": format!("{group}/{function}")
+            }),
+        );
+        write(
+            &dir,
+            "new/estimates.json",
+            json!({
+                "mean": estimate(mean),
+                "median": estimate(mean),
+                "median_abs_dev": estimate(1.0),
+                "slope": estimate(mean),
+                "std_dev": estimate(2.0)
+            }),
+        );
+        if let Some(change) = change {
+            write(
+                &dir,
+                "change/estimates.json",
+                json!({"mean": estimate(change), "median": estimate(change)}),
+            );
+        }
+
+        dir
+    }
+
+    #[test]
+    fn convert_directory() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("ctrf-rs-criterion-{}", Uuid::new_v4()));
+        bench(&root, "fib", "20", 25_000_000.0, Some(0.02));
+        bench(&root, "fib", "30", 3_000_000_000.0, Some(0.25));
+        bench(&root, "parse", "small", 120.0, None);
+        fs::create_dir_all(root.join("report")).unwrap();
+
+        let tests = read_dir(&root, &CriterionOptions::default())?;
+        fs::remove_dir_all(&root)?;
+
+        let names: Vec<_> = tests.iter().map(Test::name).collect();
+        assert_eq!(names, ["fib/20", "fib/30", "parse/small"]);
+        assert!(tests
+            .iter()
+            .all(|t| t.test_type.as_deref() == Some(BENCHMARK_TEST_TYPE)));
+        assert_eq!(tests[0].status(), Status::Passed);
+        assert_eq!(tests[0].duration(), Duration::from_millis(25));
+        assert_eq!(tests[0].suite().as_deref(), Some("fib"));
+        assert_eq!(tests[1].status(), Status::Failed);
+        assert_eq!(
+            tests[1].message.as_deref(),
+            Some("mean time regressed by 25.00% (at least 22.50%), above the threshold of 5.00%")
+        );
+
+        let measurement = tests[2].get_extension::<BenchmarkMeasurement>()?.unwrap();
+        assert_eq!(measurement.mean, 120.0);
+        assert_eq!(measurement.std_dev, 2.0);
+        assert_eq!(measurement.change, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn threshold() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("ctrf-rs-criterion-{}", Uuid::new_v4()));
+        let dir = bench(&root, "fib", "20", 100.0, Some(0.25));
+        let options = CriterionOptions {
+            regression_threshold: 0.5,
+        };
+
+        let test = read_benchmark(&dir, &options)?.unwrap();
+        fs::remove_dir_all(&root)?;
+
+        assert_eq!(test.status(), Status::Passed);
+        assert_eq!(
+            test.get_extension::<BenchmarkMeasurement>()?
+                .unwrap()
+                .change
+                .unwrap()
+                .mean,
+            0.25
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn threshold_within_confidence_interval() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("ctrf-rs-criterion-{}", Uuid::new_v4()));
+        // The point estimate of 5.5% exceeds the threshold, its lower bound of 4.95% does not
+        let dir = bench(&root, "fib", "20", 100.0, Some(0.055));
+
+        let test = read_benchmark(&dir, &CriterionOptions::default())?.unwrap();
+        fs::remove_dir_all(&root)?;
+
+        assert_eq!(test.status(), Status::Passed);
+
+        Ok(())
+    }
+}
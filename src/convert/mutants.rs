@@ -0,0 +1,246 @@
+use crate::{
+    error::{Error, Result},
+    extra::Extra,
+    test::{Status, Test},
+};
+
+use std::{io::Read, path::PathBuf, time::Duration};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// `test_type` of the converted Tests
+pub const MUTATION_TEST_TYPE: &str = "mutation";
+
+/// Key of a converted Test's `extra` map holding the mutant as described by cargo-mutants
+pub const MUTANT_KEY: &str = "mutant";
+
+#[derive(Deserialize)]
+struct Outcomes {
+    outcomes: Vec<Outcome>,
+}
+
+#[derive(Deserialize)]
+struct Outcome {
+    scenario: Value,
+    summary: String,
+    #[serde(default)]
+    phase_results: Vec<PhaseResult>,
+}
+
+#[derive(Deserialize)]
+struct PhaseResult {
+    /// Seconds
+    duration: f64,
+}
+
+#[derive(Deserialize)]
+struct Mutant {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    package: Option<String>,
+    file: String,
+    #[serde(default)]
+    function: Option<Function>,
+    span: Span,
+    replacement: String,
+    #[serde(default)]
+    genre: Option<String>,
+    #[serde(default)]
+    original: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Function {
+    function_name: String,
+    #[serde(default)]
+    return_type: String,
+}
+
+#[derive(Deserialize)]
+struct Span {
+    start: LineColumn,
+}
+
+#[derive(Deserialize)]
+struct LineColumn {
+    line: usize,
+    column: usize,
+}
+
+impl Mutant {
+    /// The description cargo-mutants prints, unless the outcome carries its own
+    fn name(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+
+        let location = format!(
+            "{}:{}:{}",
+            self.file, self.span.start.line, self.span.start.column
+        );
+        let function = self.function.as_ref();
+        match (self.genre.as_deref(), function) {
+            (Some("FnValue") | None, Some(function)) => format!(
+                "{location}: replace {} with {}",
+                [
+                    function.function_name.as_str(),
+                    function.return_type.as_str()
+                ]
+                .join(" ")
+                .trim_end(),
+                self.replacement
+            ),
+            (_, function) => {
+                let mut name = format!(
+                    "{location}: replace {} with {}",
+                    self.original.as_deref().unwrap_or("expression"),
+                    self.replacement
+                );
+                if let Some(function) = function {
+                    name.push_str(&format!(" in {}", function.function_name));
+                }
+                name
+            }
+        }
+    }
+}
+
+/// Converts the `outcomes.json` written by cargo-mutants into one Test per mutant.
+/// Caught mutants pass, missed mutants fail, and the others, such as timeouts and unviable
+/// mutants, have status other.  The outcome is kept as `raw_status` and the baseline is skipped.
+pub fn from_slice(s: &[u8]) -> Result<Vec<Test>> {
+    let mut deserializer = serde_json::Deserializer::from_slice(s);
+    let outcomes: Outcomes =
+        serde_path_to_error::deserialize(&mut deserializer).map_err(|e| Error::tracked("$", e))?;
+
+    let mut tests = vec![];
+    for (index, outcome) in outcomes.outcomes.into_iter().enumerate() {
+        let raw = match outcome.scenario.get("Mutant") {
+            Some(mutant) => mutant.clone(),
+            // The unmutated baseline
+            None => continue,
+        };
+        let mutant = Mutant::deserialize(&raw)
+            .map_err(|e| Error::schema(format!("$.outcomes[{index}].scenario.Mutant"), e))?;
+
+        let status = match outcome.summary.as_str() {
+            "CaughtMutant" => Status::Passed,
+            "MissedMutant" => Status::Failed,
+            _ => Status::Other,
+        };
+        let seconds: f64 = outcome.phase_results.iter().map(|p| p.duration).sum();
+
+        let mut test = Test::new(
+            mutant.name(),
+            status,
+            Duration::from_secs_f64(seconds.max(0.0)),
+        );
+        test.suite = mutant.package.clone();
+        test.filepath = Some(PathBuf::from(&mutant.file));
+        test.line = Some(mutant.span.start.line);
+        test.raw_status = Some(outcome.summary);
+        test.test_type = Some(String::from(MUTATION_TEST_TYPE));
+        if status == Status::Failed {
+            test.message = Some(String::from("mutant was not caught by any test"));
+        }
+        test.set_extra(MUTANT_KEY, &raw)?;
+
+        tests.push(test);
+    }
+
+    Ok(tests)
+}
+
+/// Converts cargo-mutants outcomes read from an I/O stream, see [`from_slice`]
+pub fn from_reader(mut r: impl Read) -> Result<Vec<Test>> {
+    let mut buf = vec![];
+    r.read_to_end(&mut buf)?;
+
+    from_slice(&buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn outcome(summary: &str, genre: &str, line: usize) -> Value {
+        json!({
+            "scenario": {
+                "Mutant": {
+                    "package": "calc",
+                    "file": "src/ops.rs",
+                    "function": {
+                        "function_name": "add",
+                        "return_type": "-> i32",
+                        "span": {"start": {"line": 3, "column": 1}, "end": {"line": 5, "column": 2}}
+                    },
+                    "span": {"start": {"line": line, "column": 9}, "end": {"line": line, "column": 10}},
+                    "replacement": if genre == "FnValue" { "0" } else { "-" },
+                    "genre": genre
+                }
+            },
+            "summary": summary,
+            "log_path": "log/src/ops.rs_line_4.log",
+            "phase_results": [
+                {"phase": "Build", "duration": 1.5, "process_status": "Success", "argv": []},
+                {"phase": "Test", "duration": 0.25, "process_status": "Failure", "argv": []}
+            ]
+        })
+    }
+
+    #[test]
+    fn convert_outcomes() -> Result<()> {
+        let outcomes = json!({
+            "outcomes": [
+                {"scenario": "Baseline", "summary": "Success", "phase_results": []},
+                outcome("CaughtMutant", "FnValue", 4),
+                outcome("MissedMutant", "BinaryOperator", 4),
+                outcome("Timeout", "FnValue", 4),
+                outcome("Unviable", "FnValue", 4)
+            ],
+            "total_mutants": 4,
+            "missed": 1,
+            "caught": 1,
+            "timeout": 1,
+            "unviable": 1
+        });
+
+        let tests = from_slice(outcomes.to_string().as_bytes())?;
+
+        let statuses: Vec<_> = tests.iter().map(Test::status).collect();
+        assert_eq!(
+            statuses,
+            [Status::Passed, Status::Failed, Status::Other, Status::Other]
+        );
+        assert_eq!(tests[0].name(), "src/ops.rs:4:9: replace add -> i32 with 0");
+        assert_eq!(
+            tests[1].name(),
+            "src/ops.rs:4:9: replace expression with - in add"
+        );
+        assert_eq!(tests[2].raw_status.as_deref(), Some("Timeout"));
+        assert_eq!(tests[0].filepath, Some(PathBuf::from("src/ops.rs")));
+        assert_eq!(tests[0].line, Some(4));
+        assert_eq!(tests[0].suite().as_deref(), Some("calc"));
+        assert_eq!(tests[0].duration(), Duration::from_millis(1750));
+        assert_eq!(
+            tests[1].get_extra::<Value>(MUTANT_KEY)?.unwrap()["genre"],
+            "BinaryOperator"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_mutant() {
+        let outcomes = json!({
+            "outcomes": [{"scenario": {"Mutant": {"file": "src/lib.rs"}}, "summary": "CaughtMutant"}]
+        });
+
+        let error = from_slice(outcomes.to_string().as_bytes()).unwrap_err();
+        assert_eq!(error.path(), Some("$.outcomes[0].scenario.Mutant"));
+    }
+}
@@ -0,0 +1,294 @@
+use crate::{error::Result, report::Report};
+
+use std::{io::Write, str::FromStr};
+
+use serde_json::{Map, Value};
+
+/// A Report loaded with its source document, written back keeping the source's key order,
+/// unknown fields and number spelling, and matching array elements by `id`, `suite` and `name`
+#[derive(Clone, Debug)]
+pub struct Document {
+    original: Value,
+    baseline: Value,
+    report: Report,
+}
+
+impl Document {
+    /// Deserialize a Document from bytes of JSON text
+    pub fn from_slice(s: &[u8]) -> Result<Self> {
+        Self::from_value(serde_json::from_slice(s)?)
+    }
+
+    /// Interpret a `serde_json::Value` as a Document
+    pub fn from_value(original: Value) -> Result<Self> {
+        let report = Report::from_value(original.clone())?;
+        let baseline = serde_json::to_value(&report)?;
+
+        Ok(Self {
+            original,
+            baseline,
+            report,
+        })
+    }
+
+    /// Borrows the Report
+    pub fn report(&self) -> &Report {
+        &self.report
+    }
+
+    /// Mutably borrows the Report, whose changes are merged into the document on output
+    pub fn report_mut(&mut self) -> &mut Report {
+        &mut self.report
+    }
+
+    /// Returns the Report, discarding the original document
+    pub fn into_report(self) -> Report {
+        self.report
+    }
+
+    /// Outputs the document as a `serde_json::Value`
+    pub fn to_value(&self) -> Result<Value> {
+        let current = serde_json::to_value(&self.report)?;
+
+        Ok(merge(&self.original, Some(&self.baseline), current))
+    }
+
+    /// Outputs the document as a String of JSON
+    pub fn to_string(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self.to_value()?)?)
+    }
+
+    /// Outputs the document as a pretty-printed String of JSON
+    pub fn to_string_pretty(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.to_value()?)?)
+    }
+
+    /// Outputs the document as JSON to the provided I/O stream
+    pub fn to_writer(&self, writer: impl Write) -> Result<()> {
+        Ok(serde_json::to_writer(writer, &self.to_value()?)?)
+    }
+
+    /// Outputs the document as pretty-printed JSON to the provided I/O stream
+    pub fn to_writer_pretty(&self, writer: impl Write) -> Result<()> {
+        Ok(serde_json::to_writer_pretty(writer, &self.to_value()?)?)
+    }
+}
+
+impl FromStr for Document {
+    type Err = crate::error::Error;
+
+    /// Deserialize a Document from a string of JSON text
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_slice(s.as_bytes())
+    }
+}
+
+/// Three-way merge of the original document, the report as loaded (`baseline`, None where the
+/// model dropped the value) and the report as it is now
+fn merge(original: &Value, baseline: Option<&Value>, current: Value) -> Value {
+    if baseline == Some(&current) {
+        return original.clone();
+    }
+
+    match (original, current) {
+        (Value::Object(original), Value::Object(mut current)) => {
+            let baseline = baseline.and_then(Value::as_object);
+            let mut merged = Map::new();
+
+            for (key, value) in original {
+                let base = baseline.and_then(|b| b.get(key));
+                match current.remove(key) {
+                    Some(now) => {
+                        merged.insert(key.clone(), merge(value, base, now));
+                    }
+                    // Unknown to the model, so keep it
+                    None if base.is_none() => {
+                        merged.insert(key.clone(), value.clone());
+                    }
+                    // Removed from the report
+                    None => {}
+                }
+            }
+            // Keys are appended in the order they are serialized
+            merged.extend(current);
+
+            Value::Object(merged)
+        }
+        (Value::Array(original), Value::Array(current)) => {
+            let baseline = baseline.and_then(Value::as_array);
+            let mut unmatched: Vec<Option<&Value>> = original.iter().map(Some).collect();
+
+            let merged = current
+                .into_iter()
+                .enumerate()
+                .map(|(index, now)| {
+                    let key = identity(&now);
+                    let matched = match &key {
+                        Some(_) => unmatched
+                            .iter()
+                            .position(|value| value.map_or(false, |v| identity(v) == key)),
+                        None => Some(index).filter(|&i| {
+                            unmatched
+                                .get(i)
+                                .copied()
+                                .flatten()
+                                .map_or(false, |v| identity(v).is_none())
+                        }),
+                    };
+
+                    match matched.and_then(|i| Some((i, unmatched[i].take()?))) {
+                        Some((i, value)) => merge(value, baseline.and_then(|b| b.get(i)), now),
+                        None => now,
+                    }
+                })
+                .collect();
+
+            Value::Array(merged)
+        }
+        (_, current) => current,
+    }
+}
+
+/// Identifies an array element across edits by its `id`, `suite` and `name`, if it has any
+fn identity(value: &Value) -> Option<[Option<&Value>; 3]> {
+    let object = value.as_object()?;
+    let key = [object.get("id"), object.get("suite"), object.get("name")];
+
+    if key.iter().all(Option::is_none) {
+        None
+    } else {
+        Some(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{extra::Extra, test::Status};
+
+    use serde_json::json;
+
+    const FOREIGN: &str = r#"{
+  "results": {
+    "tool": {
+      "name": "jest",
+      "version": "29.7.0"
+    },
+    "summary": {
+      "tests": 1,
+      "passed": 1,
+      "failed": 0,
+      "pending": 0,
+      "skipped": 0,
+      "other": 0,
+      "start": 1700000000000,
+      "stop": 1700000000100
+    },
+    "tests": [
+      {
+        "name": "adds",
+        "status": "passed",
+        "duration": 12,
+        "extra": {
+          "zeta": 1.5,
+          "alpha": 21
+        }
+      }
+    ],
+    "unknownResultsField": [
+      1,
+      2
+    ]
+  },
+  "specVersion": "0.0.0",
+  "reportFormat": "CTRF",
+  "extra": {
+    "z": true,
+    "a": 100
+  }
+}"#;
+
+    #[test]
+    fn round_trip_is_byte_stable() -> Result<()> {
+        let document: Document = FOREIGN.parse()?;
+
+        assert_eq!(document.to_string_pretty()?, FOREIGN);
+
+        Ok(())
+    }
+
+    #[test]
+    fn changes_are_merged() -> Result<()> {
+        let mut document: Document = FOREIGN.parse()?;
+        document
+            .report_mut()
+            .extra
+            .insert(String::from("added"), Value::from("new"));
+        document.report_mut().extra.remove("z");
+
+        let output = document.to_string_pretty()?;
+        let expected = FOREIGN.replace(
+            "    \"z\": true,\n    \"a\": 100\n",
+            "    \"a\": 100,\n    \"added\": \"new\"\n",
+        );
+        assert_eq!(output, expected);
+
+        let reread = Report::from_str(&output)?;
+        assert_eq!(reread.results().tests()[0].status(), Status::Passed);
+        assert_eq!(
+            reread.results().tests()[0].get_extra::<f64>("zeta")?,
+            Some(1.5)
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    #[test]
+    fn numbers_keep_their_spelling() -> Result<()> {
+        let foreign = FOREIGN
+            .replace("\"zeta\": 1.5", "\"zeta\": 1.50")
+            .replace("\"alpha\": 21", "\"alpha\": 1e+21")
+            .replace("\"a\": 100\n", "\"a\": 100000000000000000000000\n");
+        let document: Document = foreign.parse()?;
+
+        assert_eq!(document.to_string_pretty()?, foreign);
+
+        Ok(())
+    }
+
+    #[test]
+    fn array_elements_are_matched_by_identity() {
+        let original = json!([
+            {"name": "a", "unknown": 1, "value": 1},
+            {"name": "b", "unknown": 2, "value": 2},
+            {"value": 3, "unknown": 3}
+        ]);
+        let baseline = json!([
+            {"name": "a", "value": 1},
+            {"name": "b", "value": 2},
+            {"value": 3}
+        ]);
+        let current = json!([
+            {"name": "new", "value": 0},
+            {"name": "b", "value": 2},
+            {"name": "a", "value": 10}
+        ]);
+
+        assert_eq!(
+            merge(&original, Some(&baseline), current),
+            json!([
+                {"name": "new", "value": 0},
+                {"name": "b", "unknown": 2, "value": 2},
+                {"name": "a", "unknown": 1, "value": 10}
+            ])
+        );
+
+        let current = json!([{"name": "a", "value": 1}, {"name": "b", "value": 2}, {"value": 4}]);
+        assert_eq!(
+            merge(&original, Some(&baseline), current)[2],
+            json!({"value": 4, "unknown": 3})
+        );
+    }
+}
@@ -0,0 +1,244 @@
+//! Encodings of a Report other than JSON.
+//! Reports pass through `serde_json::Value` in both directions, so every encoding carries the
+//! same data as the JSON document, and decoding is checked like [`Report::from_value`].
+//! Numbers are encoded natively, also under the `arbitrary_precision` feature.
+
+use crate::{
+    error::{Error, Result},
+    report::Report,
+};
+
+use std::{
+    fmt::Display,
+    io::{Read, Write},
+};
+
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+
+#[cfg(feature = "yaml")]
+impl Report {
+    /// Outputs the report as a String of YAML
+    pub fn to_yaml_string(&self) -> Result<String> {
+        serde_yaml::to_string(&Native(&serde_json::to_value(self)?))
+            .map_err(|e| encoding("YAML", e))
+    }
+
+    /// Outputs the report as YAML to the provided I/O stream
+    pub fn to_yaml_writer(&self, writer: impl Write) -> Result<()> {
+        serde_yaml::to_writer(writer, &Native(&serde_json::to_value(self)?))
+            .map_err(|e| encoding("YAML", e))
+    }
+
+    /// Deserialize a `Report` instance from a string of YAML text
+    pub fn from_yaml_str(s: &str) -> Result<Self> {
+        let value: Value = serde_yaml::from_str(s).map_err(|e| encoding("YAML", e))?;
+
+        Self::from_value(value)
+    }
+
+    /// Deserialize a `Report` instance from an I/O stream of YAML text
+    pub fn from_yaml_reader(r: impl Read) -> Result<Self> {
+        let value: Value = serde_yaml::from_reader(r).map_err(|e| encoding("YAML", e))?;
+
+        Self::from_value(value)
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl Report {
+    /// Outputs the report as a CBOR byte vector
+    pub fn to_cbor_vec(&self) -> Result<Vec<u8>> {
+        let mut buf = vec![];
+        self.to_cbor_writer(&mut buf)?;
+
+        Ok(buf)
+    }
+
+    /// Outputs the report as CBOR to the provided I/O stream
+    pub fn to_cbor_writer(&self, writer: impl Write) -> Result<()> {
+        ciborium::into_writer(&Native(&serde_json::to_value(self)?), writer)
+            .map_err(|e| encoding("CBOR", e))
+    }
+
+    /// Deserialize a `Report` instance from CBOR bytes
+    pub fn from_cbor_slice(s: &[u8]) -> Result<Self> {
+        Self::from_cbor_reader(s)
+    }
+
+    /// Deserialize a `Report` instance from an I/O stream of CBOR
+    pub fn from_cbor_reader(r: impl Read) -> Result<Self> {
+        let value: Value = ciborium::from_reader(r).map_err(|e| encoding("CBOR", e))?;
+
+        Self::from_value(value)
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl Report {
+    /// Outputs the report as a MessagePack byte vector, with maps keyed by field name
+    pub fn to_msgpack_vec(&self) -> Result<Vec<u8>> {
+        rmp_serde::to_vec_named(&Native(&serde_json::to_value(self)?))
+            .map_err(|e| encoding("MessagePack", e))
+    }
+
+    /// Outputs the report as MessagePack to the provided I/O stream, with maps keyed by field name
+    pub fn to_msgpack_writer(&self, mut writer: impl Write) -> Result<()> {
+        rmp_serde::encode::write_named(&mut writer, &Native(&serde_json::to_value(self)?))
+            .map_err(|e| encoding("MessagePack", e))
+    }
+
+    /// Deserialize a `Report` instance from MessagePack bytes
+    pub fn from_msgpack_slice(s: &[u8]) -> Result<Self> {
+        let value: Value = rmp_serde::from_slice(s).map_err(|e| encoding("MessagePack", e))?;
+
+        Self::from_value(value)
+    }
+
+    /// Deserialize a `Report` instance from an I/O stream of MessagePack
+    pub fn from_msgpack_reader(r: impl Read) -> Result<Self> {
+        let value: Value = rmp_serde::from_read(r).map_err(|e| encoding("MessagePack", e))?;
+
+        Self::from_value(value)
+    }
+}
+
+/// A JSON value serializing its numbers as integers or floats.  Under `arbitrary_precision`,
+/// `serde_json` serializes each number as a map only its own deserializer understands.
+#[cfg(any(feature = "yaml", feature = "cbor", feature = "msgpack"))]
+struct Native<'a>(&'a Value);
+
+#[cfg(any(feature = "yaml", feature = "cbor", feature = "msgpack"))]
+impl Serialize for Native<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self.0 {
+            Value::Number(n) => match (n.as_u64(), n.as_i64(), n.as_f64()) {
+                (Some(n), _, _) => serializer.serialize_u64(n),
+                (_, Some(n), _) => serializer.serialize_i64(n),
+                (_, _, Some(n)) => serializer.serialize_f64(n),
+                _ => serializer.serialize_str(&n.to_string()),
+            },
+            Value::Array(values) => serializer.collect_seq(values.iter().map(Native)),
+            Value::Object(map) => {
+                serializer.collect_map(map.iter().map(|(key, value)| (key, Native(value))))
+            }
+            value => value.serialize(serializer),
+        }
+    }
+}
+
+fn encoding(format: &'static str, e: impl Display) -> Error {
+    Error::Encoding {
+        format,
+        message: e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        extra::Extra,
+        results::ResultsBuilder,
+        test::{Status, Test},
+        tool::Tool,
+    };
+
+    use std::time::{Duration, SystemTime};
+
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn report() -> Report {
+        let mut test = Test::new(String::from("t"), Status::Failed, Duration::from_millis(7));
+        test.set_extra("retries", &[1, 2]).unwrap();
+        let mut builder = ResultsBuilder::new(Tool::new("ctrf-rs", None));
+        builder.add_test(test);
+        builder
+            .set_extra("nested", &json!({"a": {"b": [true, null, 1.5]}}))
+            .unwrap();
+
+        let time = SystemTime::now();
+        let mut report = Report::new(
+            Some(Uuid::new_v4()),
+            Some(time),
+            None,
+            builder.build(time, time),
+        );
+        report.extra.insert(String::from("seed"), json!(u64::MAX));
+        report
+    }
+
+    /// Whether an encoding leaked `serde_json`'s private number map under `arbitrary_precision`
+    #[cfg(any(feature = "cbor", feature = "msgpack"))]
+    fn contains(encoded: &[u8], text: &[u8]) -> bool {
+        encoded.windows(text.len()).any(|window| window == text)
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_round_trip() -> Result<()> {
+        let report = report();
+        let yaml = report.to_yaml_string()?;
+
+        assert!(yaml.contains("reportFormat: CTRF"));
+        assert_eq!(Report::from_yaml_str(&yaml)?, report);
+        assert_eq!(Report::from_yaml_reader(yaml.as_bytes())?, report);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_round_trip() -> Result<()> {
+        let report = report();
+        let cbor = report.to_cbor_vec()?;
+
+        assert_eq!(Report::from_cbor_slice(&cbor)?, report);
+        assert!(!contains(&cbor, b"serde_json"));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_round_trip() -> Result<()> {
+        let report = report();
+        let msgpack = report.to_msgpack_vec()?;
+
+        assert_eq!(Report::from_msgpack_slice(&msgpack)?, report);
+        assert!(!contains(&msgpack, b"serde_json"));
+        assert_eq!(Report::from_msgpack_reader(msgpack.as_slice())?, report);
+
+        Ok(())
+    }
+
+    /// Under `arbitrary_precision`, numbers must not be written as `serde_json`'s private map
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_numbers() -> Result<()> {
+        let yaml = report().to_yaml_string()?;
+
+        assert!(yaml.contains("\n    failed: 1\n"));
+        assert!(yaml.contains("\n  seed: 18446744073709551615\n"));
+        assert!(yaml.contains("- 1.5\n"));
+        assert!(!yaml.contains("serde_json"));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_schema_violation() {
+        let yaml = report()
+            .to_yaml_string()
+            .unwrap()
+            .replace("status: failed", "status: bogus");
+
+        assert_eq!(
+            Report::from_yaml_str(&yaml).unwrap_err().path(),
+            Some("$.results.tests[0].status")
+        );
+    }
+}
@@ -1,13 +1,18 @@
-use crate::{extra::Extra, impl_extra};
+use crate::{environment::git::GitInfo, extra::Extra, impl_extra};
 
-use std::{collections::HashMap, default::Default};
+use std::{collections::HashMap, default::Default, io, path::Path};
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+pub mod git;
+
+/// Key under which `with_git` records whether the checkout had local modifications
+pub const GIT_DIRTY_KEY: &str = "gitDirty";
+
 /// All fields are optional.  Because they number so many, normally this would lead to a builder;
 /// however, in this case we just derive `Default` and let the end implementer access as needed.
-#[derive(Deserialize, Serialize, Default, Debug, PartialEq)]
+#[derive(Deserialize, Serialize, Clone, Default, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Environment {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -47,6 +52,117 @@ impl Environment {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Creates an Environment populated from the git checkout containing `path`
+    pub fn from_git(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut environment = Self::new();
+        environment.with_git(path)?;
+
+        Ok(environment)
+    }
+
+    /// Fills the repository, commit and branch fields from the git checkout containing `path`.
+    /// The dirty state, when known, is recorded in `extra` under [`GIT_DIRTY_KEY`].
+    pub fn with_git(&mut self, path: impl AsRef<Path>) -> io::Result<&mut Self> {
+        let info = GitInfo::read(path)?;
+
+        self.repository_name = info.repository_name().or(self.repository_name.take());
+        self.repository_url = info.remote_url.or(self.repository_url.take());
+        self.commit = info.commit.or(self.commit.take());
+        self.branch_name = info.branch.or(self.branch_name.take());
+        if let Some(dirty) = info.dirty {
+            self.insert_extra(String::from(GIT_DIRTY_KEY), Value::Bool(dirty));
+        }
+
+        Ok(self)
+    }
+
+    /// Fills the OS fields from the host running the tests.
+    /// `os_release` and `os_version` are only available on Linux, where they are read from `/proc`.
+    pub fn with_host_os(&mut self) -> &mut Self {
+        self.os_platform = Some(String::from(std::env::consts::OS));
+
+        #[cfg(target_os = "linux")]
+        {
+            let read = |p| {
+                std::fs::read_to_string(p)
+                    .ok()
+                    .map(|s| String::from(s.trim()))
+            };
+            self.os_release = read("/proc/sys/kernel/osrelease").or(self.os_release.take());
+            self.os_version = read("/proc/sys/kernel/version").or(self.os_version.take());
+        }
+
+        self
+    }
 }
 
 impl_extra!(Environment);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use uuid::Uuid;
+
+    const COMMIT: &str = "0123456789abcdef0123456789abcdef01234567";
+
+    #[test]
+    fn with_git() -> io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("ctrf-rs-env-{}", Uuid::new_v4()));
+        fs::create_dir_all(dir.join(".git/refs/heads"))?;
+        fs::write(dir.join(".git/HEAD"), "ref: refs/heads/main\n")?;
+        fs::write(dir.join(".git/refs/heads/main"), format!("{COMMIT}\n"))?;
+        fs::write(
+            dir.join(".git/config"),
+            "[remote \"origin\"]\n\turl = https://example.com/team/app.git\n",
+        )?;
+        // An index without entries, so the checkout is clean
+        let mut index = Vec::from(&b"DIRC"[..]);
+        index.extend(2_u32.to_be_bytes());
+        index.extend(0_u32.to_be_bytes());
+        fs::write(dir.join(".git/index"), index)?;
+
+        let mut environment = Environment {
+            build_name: Some(String::from("nightly")),
+            ..Environment::default()
+        };
+        environment.with_git(&dir)?;
+
+        assert_eq!(environment.repository_name.as_deref(), Some("app"));
+        assert_eq!(
+            environment.repository_url.as_deref(),
+            Some("https://example.com/team/app.git")
+        );
+        assert_eq!(environment.commit.as_deref(), Some(COMMIT));
+        assert_eq!(environment.branch_name.as_deref(), Some("main"));
+        assert_eq!(environment.build_name.as_deref(), Some("nightly"));
+        assert_eq!(environment.extra[GIT_DIRTY_KEY], Value::Bool(false));
+        assert_eq!(Environment::from_git(&dir)?.commit.as_deref(), Some(COMMIT));
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn with_git_outside_checkout() {
+        let dir = std::env::temp_dir().join(format!("ctrf-rs-env-{}", Uuid::new_v4()));
+
+        assert!(Environment::new().with_git(dir).is_err());
+    }
+
+    #[test]
+    fn with_host_os() {
+        let mut environment = Environment::new();
+        environment.with_host_os();
+
+        assert_eq!(
+            environment.os_platform.as_deref(),
+            Some(std::env::consts::OS)
+        );
+        #[cfg(target_os = "linux")]
+        assert!(environment.os_release.is_some());
+    }
+}
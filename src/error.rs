@@ -0,0 +1,128 @@
+use crate::report::{REPORT_FORMAT, SPEC_VERSION};
+
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+};
+
+use semver::Version;
+use serde_json::error::Category;
+
+/// Result type for fallible operations of this crate
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors raised while reading, writing or checking CTRF reports.
+/// JSON paths are given in `$.results.tests[0].status` notation.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// `reportFormat` is not [`REPORT_FORMAT`]
+    Format { found: String },
+    /// `specVersion` is not supported by this crate, see [`SPEC_VERSION`]
+    UnsupportedSpecVersion { found: Version },
+    /// Reading or writing failed
+    Io(io::Error),
+    /// The input is not well-formed JSON, or ends early
+    Syntax {
+        path: String,
+        source: serde_json::Error,
+    },
+    /// The document does not match the CTRF schema
+    Schema {
+        path: String,
+        source: serde_json::Error,
+    },
+    /// The document is well-formed but its contents contradict each other
+    Consistency { path: String, message: String },
+    /// Encoding or decoding a format other than JSON failed
+    Encoding {
+        format: &'static str,
+        message: String,
+    },
+    /// A user-supplied pattern is invalid
+    Pattern { pattern: String, message: String },
+}
+
+impl Error {
+    /// Creates a Schema error, or an Io or Syntax error if `source` is not about the data
+    pub(crate) fn schema(path: String, source: serde_json::Error) -> Self {
+        match source.classify() {
+            Category::Io => Self::Io(source.into()),
+            Category::Syntax | Category::Eof => Self::Syntax { path, source },
+            Category::Data => Self::Schema { path, source },
+        }
+    }
+
+    /// Creates an error from a deserialization failure, locating it by the tracked path
+    /// relative to `prefix`
+    pub(crate) fn tracked(prefix: &str, e: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let inner = e.path().to_string();
+        let path = if inner == "." {
+            String::from(prefix)
+        } else if inner.starts_with('[') {
+            format!("{prefix}{inner}")
+        } else {
+            format!("{prefix}.{inner}")
+        };
+
+        Self::schema(path, e.into_inner())
+    }
+
+    /// Returns the JSON path the error relates to, if any
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Self::Format { .. } => Some("$.reportFormat"),
+            Self::UnsupportedSpecVersion { .. } => Some("$.specVersion"),
+            Self::Io(_) | Self::Encoding { .. } | Self::Pattern { .. } => None,
+            Self::Syntax { path, .. }
+            | Self::Schema { path, .. }
+            | Self::Consistency { path, .. } => Some(path),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Format { found } => write!(
+                f,
+                "unrecognized report format '{found}', expected '{REPORT_FORMAT}'"
+            ),
+            Self::UnsupportedSpecVersion { found } => write!(
+                f,
+                "unsupported spec version {found}, expected a version compatible with {SPEC_VERSION}"
+            ),
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            Self::Syntax { path, source } => write!(f, "malformed JSON at {path}: {source}"),
+            Self::Schema { path, source } => write!(f, "schema violation at {path}: {source}"),
+            Self::Consistency { path, message } => write!(f, "inconsistency at {path}: {message}"),
+            Self::Encoding { format, message } => write!(f, "{format} error: {message}"),
+            Self::Pattern { pattern, message } => {
+                write!(f, "invalid pattern '{pattern}': {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Syntax { source, .. } | Self::Schema { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    /// Errors without location information are attributed to the document root
+    fn from(e: serde_json::Error) -> Self {
+        Self::schema(String::from("$"), e)
+    }
+}
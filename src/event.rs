@@ -0,0 +1,384 @@
+use crate::{
+    environment::Environment,
+    error::{Error, Result},
+    extra::Extra,
+    report::Report,
+    results::ResultsBuilder,
+    summary::Summary,
+    test::{Status, Test},
+    tool::Tool,
+};
+
+use std::{
+    io::{BufRead, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Progress of a test run, serialized as one line of NDJSON per event.
+/// Times are in milliseconds since the Unix epoch.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum Event {
+    RunStarted {
+        tool: Tool,
+        start: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        environment: Option<Box<Environment>>,
+    },
+    TestStarted {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        suite: Option<String>,
+        start: u64,
+    },
+    TestFinished {
+        test: Box<Test>,
+    },
+    RunFinished {
+        summary: Summary,
+    },
+}
+
+impl Event {
+    /// Creates a RunStarted event stamped with the current time
+    pub fn run_started(tool: &Tool, environment: Option<&Environment>) -> Self {
+        Self::RunStarted {
+            tool: tool.clone(),
+            start: now_millis(),
+            environment: environment.cloned().map(Box::new),
+        }
+    }
+
+    /// Creates a TestStarted event stamped with the current time
+    pub fn test_started(name: &str, suite: Option<&str>) -> Self {
+        Self::TestStarted {
+            name: String::from(name),
+            suite: suite.map(String::from),
+            start: now_millis(),
+        }
+    }
+
+    pub fn test_finished(test: &Test) -> Self {
+        Self::TestFinished {
+            test: Box::new(test.clone()),
+        }
+    }
+
+    pub fn run_finished(summary: &Summary) -> Self {
+        Self::RunFinished {
+            summary: summary.clone(),
+        }
+    }
+}
+
+/// Writes Events as NDJSON.  Each event is written with a single write call and flushed,
+/// so a reader tailing the stream sees at most one torn line.
+pub struct EventWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> EventWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Writes an Event as a single line
+    pub fn write(&mut self, event: &Event) -> Result<()> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+        self.writer.write_all(&line)?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Returns the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// A test that has started but not yet finished
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunningTest {
+    pub name: String,
+    pub suite: Option<String>,
+    pub start: u64,
+}
+
+/// Folds a stream of Events into the state of the run, from which a Report can be produced at any
+/// point, e.g. to observe a run that is still in progress.
+#[derive(Default)]
+pub struct EventFold {
+    tool: Option<Tool>,
+    start: u64,
+    /// Latest time recorded by an event
+    latest: u64,
+    environment: Option<Environment>,
+    tests: Vec<Test>,
+    running: Vec<RunningTest>,
+    summary: Option<Summary>,
+}
+
+impl EventFold {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Folds all events read from an NDJSON stream.
+    /// A torn final line, as left by a writer that is still running or has died, is ignored.
+    pub fn read(reader: impl BufRead) -> Result<Self> {
+        let mut fold = Self::new();
+        // Split bytes rather than lines, as a torn line may end within a UTF-8 character
+        let mut lines = reader.split(b'\n').enumerate().peekable();
+
+        while let Some((index, line)) = lines.next() {
+            let line = line?;
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+
+            match serde_json::from_slice(&line) {
+                Ok(event) => fold.apply(event),
+                Err(_) if lines.peek().is_none() => break,
+                Err(e) => return Err(Error::schema(format!("$[{index}]"), e)),
+            }
+        }
+
+        Ok(fold)
+    }
+
+    /// Applies a single Event
+    pub fn apply(&mut self, event: Event) {
+        let time = match &event {
+            Event::RunStarted { start, .. } | Event::TestStarted { start, .. } => Some(*start),
+            Event::TestFinished { test } => test
+                .start
+                .map(|start| start + test.duration().as_millis() as u64),
+            Event::RunFinished { summary } => Some(summary.stop()),
+        };
+        self.latest = self.latest.max(time.unwrap_or_default());
+
+        match event {
+            Event::RunStarted {
+                tool,
+                start,
+                environment,
+            } => {
+                self.tool = Some(tool);
+                self.start = start;
+                self.environment = environment.map(|e| *e);
+            }
+            Event::TestStarted { name, suite, start } => {
+                self.running.push(RunningTest { name, suite, start })
+            }
+            Event::TestFinished { test } => {
+                if let Some(pos) = self
+                    .running
+                    .iter()
+                    .position(|r| r.name == test.name() && &r.suite == test.suite())
+                {
+                    self.running.remove(pos);
+                }
+                self.tests.push(*test);
+            }
+            Event::RunFinished { summary } => self.summary = Some(summary),
+        }
+    }
+
+    /// Returns true once a RunFinished event has been applied
+    pub fn is_finished(&self) -> bool {
+        self.summary.is_some()
+    }
+
+    /// Returns the latest time recorded by the events applied so far
+    pub fn latest(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.latest)
+    }
+
+    /// Borrows the tests that have started but not yet finished
+    pub fn running(&self) -> &[RunningTest] {
+        &self.running
+    }
+
+    /// Turns every running test into a failed Test with the given message and raw status,
+    /// e.g. after the run was aborted.  `stop` is used to derive their durations.
+    pub fn fail_running(&mut self, message: &str, raw_status: &str, stop: SystemTime) {
+        let stop = millis(stop);
+
+        for running in self.running.drain(..) {
+            let mut test = Test::new(
+                running.name,
+                Status::Failed,
+                Duration::from_millis(stop.saturating_sub(running.start)),
+            );
+            test.suite = running.suite;
+            test.start = Some(running.start);
+            test.message = Some(String::from(message));
+            test.raw_status = Some(String::from(raw_status));
+            self.tests.push(test);
+        }
+    }
+
+    /// Produces a Report of the finished tests.
+    /// The stop time and `extra` are taken from the RunFinished summary, or else the stop time is
+    /// `stop`.  Fails if the stop time precedes the start of the run.
+    pub fn report(&self, stop: SystemTime) -> Result<Report> {
+        let tool = self.tool.clone().ok_or_else(|| Error::Consistency {
+            path: String::from("$[0]"),
+            message: String::from("event stream does not begin with a runStarted event"),
+        })?;
+
+        let (stop, source) = match &self.summary {
+            Some(summary) => (summary.stop(), "the runFinished summary"),
+            None => (millis(stop), "the given stop time"),
+        };
+        if stop < self.start {
+            return Err(Error::Consistency {
+                path: String::from("$"),
+                message: format!(
+                    "run stops at {stop} ms according to {source}, before it started at {} ms",
+                    self.start
+                ),
+            });
+        }
+        let start = UNIX_EPOCH + Duration::from_millis(self.start);
+        let stop = UNIX_EPOCH + Duration::from_millis(stop);
+
+        let mut builder = ResultsBuilder::new(tool);
+        for test in &self.tests {
+            builder.add_test(test.clone());
+        }
+        builder.environment(self.environment.clone());
+
+        let mut results = builder.build(start, stop);
+        if let Some(summary) = &self.summary {
+            for (key, value) in summary.iter_extra() {
+                results
+                    .summary_mut()
+                    .insert_extra(key.clone(), value.clone());
+            }
+        }
+
+        Ok(Report::new(None, None, None, results))
+    }
+}
+
+fn now_millis() -> u64 {
+    millis(SystemTime::now())
+}
+
+fn millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold_event_stream() -> Result<()> {
+        let tool = Tool::new("ctrf-rs", None);
+        let mut passed = Test::new(String::from("a"), Status::Passed, Duration::from_millis(2));
+        passed.assign_id();
+        let failed = Test::new(String::from("b"), Status::Failed, Duration::from_millis(4));
+        let time = SystemTime::now();
+        let mut summary = Summary::new(time, time + Duration::from_secs(1));
+        summary.passed(1);
+        summary.failed(1);
+
+        let mut writer = EventWriter::new(vec![]);
+        writer.write(&Event::run_started(&tool, None))?;
+        writer.write(&Event::test_started("a", None))?;
+        writer.write(&Event::test_started("b", None))?;
+        writer.write(&Event::test_finished(&passed))?;
+
+        let live = EventFold::read(writer.into_inner().as_slice())?;
+        assert!(!live.is_finished());
+        assert_eq!(live.running().len(), 1);
+        assert_eq!(live.running()[0].name, "b");
+        assert_eq!(
+            live.report(SystemTime::now())?.results().tests(),
+            [passed.clone()]
+        );
+
+        let mut writer = EventWriter::new(vec![]);
+        for event in [
+            Event::run_started(&tool, None),
+            Event::test_finished(&passed),
+            Event::test_finished(&failed),
+            Event::run_finished(&summary),
+        ] {
+            writer.write(&event)?;
+        }
+
+        let finished = EventFold::read(writer.into_inner().as_slice())?;
+        let report = finished.report(SystemTime::now())?;
+        assert!(finished.is_finished());
+        assert_eq!(report.results().summary().count(Status::Failed), 1);
+        assert_eq!(report.results().summary().stop(), summary.stop());
+
+        Ok(())
+    }
+
+    #[test]
+    fn stop_before_start() -> Result<()> {
+        let tool = Tool::new("ctrf-rs", None);
+        let mut writer = EventWriter::new(vec![]);
+        writer.write(&Event::run_started(&tool, None))?;
+        let stream = writer.into_inner();
+
+        let fold = EventFold::read(stream.as_slice())?;
+        assert!(matches!(
+            fold.report(UNIX_EPOCH),
+            Err(Error::Consistency { .. })
+        ));
+
+        let mut writer = EventWriter::new(stream);
+        writer.write(&Event::run_finished(&Summary::new(UNIX_EPOCH, UNIX_EPOCH)))?;
+        let fold = EventFold::read(writer.into_inner().as_slice())?;
+        let error = fold.report(SystemTime::now()).unwrap_err();
+        assert!(error.to_string().contains("runFinished"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn torn_multibyte_line() -> Result<()> {
+        let test = Test::new(
+            String::from("é测试"),
+            Status::Passed,
+            Duration::from_millis(1),
+        );
+        let mut writer = EventWriter::new(vec![]);
+        writer.write(&Event::run_started(&Tool::new("ctrf-rs", None), None))?;
+        writer.write(&Event::test_finished(&test))?;
+        let stream = writer.into_inner();
+        // Cut after the first byte of "测"
+        let torn = stream
+            .windows(3)
+            .position(|w| w == "测".as_bytes())
+            .unwrap()
+            + 1;
+
+        let fold = EventFold::read(&stream[..torn])?;
+        assert!(fold.report(SystemTime::now())?.results().tests().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn event_lines() -> Result<()> {
+        let event = Event::test_started("a", Some("s"));
+        let line = serde_json::to_string(&event)?;
+
+        assert!(line.starts_with(r#"{"event":"testStarted","name":"a","suite":"s""#));
+        assert_eq!(serde_json::from_str::<Event>(&line)?, event);
+
+        Ok(())
+    }
+}
@@ -1,8 +1,82 @@
+use crate::error::{Error, Result};
+
+use std::collections::{hash_map::Iter, HashMap};
+
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
 
+/// Access to the `extra` map of a CTRF element.
+/// Errors from the typed accessors carry the path `$.extra.<key>`, relative to the owning element.
 pub trait Extra {
     fn insert_extra(&mut self, key: String, value: Value) -> Option<Value>;
     fn remove_extra(&mut self, key: &str) -> Option<Value>;
+    fn extra_map(&self) -> &HashMap<String, Value>;
+    fn extra_map_mut(&mut self) -> &mut HashMap<String, Value>;
+
+    /// Returns the value at the provided key, deserialized as `T`.
+    /// Returns None if no value is present, or an error if it does not match `T`.
+    fn get_extra<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        self.extra_map()
+            .get(key)
+            .map(T::deserialize)
+            .transpose()
+            .map_err(|e| extra_error(key, e))
+    }
+
+    /// Serializes `value` and inserts it at the provided key.
+    /// Returns the raw value that it replaced, if one was present.
+    fn set_extra<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<Option<Value>> {
+        let value = serde_json::to_value(value).map_err(|e| extra_error(key, e))?;
+
+        Ok(self.insert_extra(String::from(key), value))
+    }
+
+    /// Iterates over all raw key/value pairs, in arbitrary order
+    fn iter_extra(&self) -> Iter<'_, String, Value> {
+        self.extra_map().iter()
+    }
+
+    /// Returns the extension stored under its registered key, if present
+    fn get_extension<E: ExtraExtension>(&self) -> Result<Option<E>> {
+        self.get_extra(E::KEY)
+    }
+
+    /// Stores the extension under its registered key, returning the raw value it replaced
+    fn set_extension<E: ExtraExtension>(&mut self, extension: &E) -> Result<Option<Value>> {
+        self.set_extra(E::KEY, extension)
+    }
+
+    /// Removes and returns the extension stored under its registered key, if present.
+    /// A value that does not match `E` is left in place and reported as an error.
+    fn remove_extension<E: ExtraExtension>(&mut self) -> Result<Option<E>> {
+        let extension = self.get_extension::<E>()?;
+        self.remove_extra(E::KEY);
+
+        Ok(extension)
+    }
+}
+
+fn extra_error(key: &str, e: serde_json::Error) -> Error {
+    Error::schema(format!("$.extra.{key}"), e)
+}
+
+/// A custom metadata type that lives under a fixed key of an `extra` map.
+///
+/// ```
+/// use ctrf_rs::extra::ExtraExtension;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct MyTeamMeta {
+///     owner: String,
+/// }
+///
+/// impl ExtraExtension for MyTeamMeta {
+///     const KEY: &'static str = "myTeam";
+/// }
+/// ```
+pub trait ExtraExtension: Serialize + DeserializeOwned {
+    const KEY: &'static str;
 }
 
 #[macro_export]
@@ -20,6 +94,111 @@ macro_rules! impl_extra {
             fn remove_extra(&mut self, key: &str) -> Option<Value> {
                 self.extra.remove(key)
             }
+
+            /// Borrows the Extra map.
+            fn extra_map(&self) -> &::std::collections::HashMap<String, Value> {
+                &self.extra
+            }
+
+            /// Mutably borrows the Extra map.
+            fn extra_map_mut(&mut self) -> &mut ::std::collections::HashMap<String, Value> {
+                &mut self.extra
+            }
         }
     )+)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tool::Tool;
+
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct TeamMeta {
+        owner: String,
+        priority: u8,
+    }
+
+    impl ExtraExtension for TeamMeta {
+        const KEY: &'static str = "team";
+    }
+
+    #[test]
+    fn typed_round_trip() -> Result<()> {
+        let mut tool = Tool::new("ctrf-rs", None);
+
+        assert_eq!(tool.set_extra("retries", &3_u32)?, None);
+        assert_eq!(tool.get_extra::<u32>("retries")?, Some(3));
+        assert_eq!(tool.get_extra::<u32>("absent")?, None);
+        assert!(tool.get_extra::<String>("retries").is_err());
+        assert_eq!(tool.iter_extra().count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn extension_round_trip() -> Result<()> {
+        let mut tool = Tool::new("ctrf-rs", None);
+        let meta = TeamMeta {
+            owner: String::from("net"),
+            priority: 1,
+        };
+
+        tool.set_extension(&meta)?;
+
+        assert_eq!(tool.extra_map()["team"]["owner"], "net");
+        assert_eq!(tool.get_extension::<TeamMeta>()?, Some(meta));
+        assert!(tool.remove_extension::<TeamMeta>()?.is_some());
+        assert_eq!(tool.get_extension::<TeamMeta>()?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn remove_mismatched_extension() -> Result<()> {
+        let mut tool = Tool::new("ctrf-rs", None);
+        tool.set_extra("team", "net")?;
+
+        let error = tool.remove_extension::<TeamMeta>().unwrap_err();
+
+        assert_eq!(error.path(), Some("$.extra.team"));
+        assert_eq!(tool.get_extra::<String>("team")?.as_deref(), Some("net"));
+
+        Ok(())
+    }
+
+    /// An implementation outside [`impl_extra`], as written by users of the crate
+    struct Custom(HashMap<String, Value>);
+
+    impl Extra for Custom {
+        fn insert_extra(&mut self, key: String, value: Value) -> Option<Value> {
+            self.0.insert(key, value)
+        }
+
+        fn remove_extra(&mut self, key: &str) -> Option<Value> {
+            self.0.remove(key)
+        }
+
+        fn extra_map(&self) -> &HashMap<String, Value> {
+            &self.0
+        }
+
+        fn extra_map_mut(&mut self) -> &mut HashMap<String, Value> {
+            &mut self.0
+        }
+    }
+
+    #[test]
+    fn custom_implementor() -> Result<()> {
+        let mut custom = Custom(HashMap::new());
+
+        assert_eq!(custom.set_extra("retries", &3_u32)?, None);
+        assert_eq!(custom.get_extra::<u32>("retries")?, Some(3));
+        assert_eq!(custom.iter_extra().count(), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,354 @@
+use crate::{
+    error::{Error, Result},
+    results::Results,
+    test::Status,
+};
+
+use std::{collections::HashSet, io::Read, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// Exit code of a run that passed every gate
+pub const EXIT_PASSED: i32 = 0;
+/// Exit code of a run that failed a gate
+pub const EXIT_FAILED: i32 = 1;
+
+/// Thresholds a run must meet, each optional.
+/// Keys are read in camelCase as well as snake_case, and unknown keys are rejected.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GateConfig {
+    /// Most failed tests allowed
+    #[serde(alias = "max_failed", skip_serializing_if = "Option::is_none")]
+    pub max_failed: Option<usize>,
+    /// Highest share of failed tests allowed, from 0 to 1
+    #[serde(alias = "max_failure_rate", skip_serializing_if = "Option::is_none")]
+    pub max_failure_rate: Option<f64>,
+    /// Highest share of skipped tests allowed, from 0 to 1
+    #[serde(alias = "max_skipped_ratio", skip_serializing_if = "Option::is_none")]
+    pub max_skipped_ratio: Option<f64>,
+    /// Longest run allowed from the summary's start to its stop, in milliseconds
+    #[serde(alias = "max_duration", skip_serializing_if = "Option::is_none")]
+    pub max_duration: Option<u64>,
+    /// Suites that must contain at least one test
+    #[serde(
+        default,
+        alias = "required_suites",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub required_suites: Vec<String>,
+    /// Fewest tests allowed, guarding against runs that silently ran nothing
+    #[serde(alias = "min_tests", skip_serializing_if = "Option::is_none")]
+    pub min_tests: Option<usize>,
+}
+
+impl GateConfig {
+    /// Deserialize a GateConfig from bytes of JSON text
+    pub fn from_slice(s: &[u8]) -> Result<Self> {
+        let mut deserializer = serde_json::Deserializer::from_slice(s);
+        let config = serde_path_to_error::deserialize(&mut deserializer)
+            .map_err(|e| Error::tracked("$", e))?;
+        deserializer.end()?;
+
+        Ok(config)
+    }
+
+    /// Deserialize a GateConfig from an I/O stream of JSON text
+    pub fn from_reader(mut r: impl Read) -> Result<Self> {
+        let mut buf = vec![];
+        r.read_to_end(&mut buf)?;
+
+        Self::from_slice(&buf)
+    }
+
+    /// Deserialize a GateConfig from a string of TOML text
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(s: &str) -> Result<Self> {
+        toml::from_str(s).map_err(|e| Error::Encoding {
+            format: "TOML",
+            message: e.to_string(),
+        })
+    }
+
+    /// Checks the Results against every configured threshold
+    pub fn evaluate(&self, results: &Results) -> Verdict {
+        let summary = results.summary();
+        let tests = summary.tests();
+        let ratio = |count: usize| {
+            if tests == 0 {
+                0.0
+            } else {
+                count as f64 / tests as f64
+            }
+        };
+        let failed = summary.count(Status::Failed);
+        let skipped = summary.count(Status::Skipped);
+        let mut rules = vec![];
+
+        if let Some(max) = self.max_failed {
+            rules.push(RuleResult::at_most(
+                Rule::MaxFailed,
+                failed as f64,
+                max as f64,
+                format!("{failed} failed tests, at most {max} allowed"),
+            ));
+        }
+        if let Some(max) = self.max_failure_rate {
+            let rate = ratio(failed);
+            rules.push(RuleResult::at_most(
+                Rule::MaxFailureRate,
+                rate,
+                max,
+                format!(
+                    "{:.2}% of tests failed, at most {:.2}% allowed",
+                    rate * 100.0,
+                    max * 100.0
+                ),
+            ));
+        }
+        if let Some(max) = self.max_skipped_ratio {
+            let rate = ratio(skipped);
+            rules.push(RuleResult::at_most(
+                Rule::MaxSkippedRatio,
+                rate,
+                max,
+                format!(
+                    "{:.2}% of tests were skipped, at most {:.2}% allowed",
+                    rate * 100.0,
+                    max * 100.0
+                ),
+            ));
+        }
+        if let Some(max) = self.max_duration {
+            let duration = summary.stop().saturating_sub(summary.start());
+            rules.push(RuleResult::at_most(
+                Rule::MaxDuration,
+                duration as f64,
+                max as f64,
+                format!("run took {duration} ms, at most {max} ms allowed"),
+            ));
+        }
+        if !self.required_suites.is_empty() {
+            let present: HashSet<&str> = results
+                .tests()
+                .iter()
+                .filter_map(|t| t.suite().as_deref())
+                .collect();
+            let missing: Vec<&str> = self
+                .required_suites
+                .iter()
+                .map(String::as_str)
+                .filter(|suite| !present.contains(suite))
+                .collect();
+            let required = self.required_suites.len();
+            let message = if missing.is_empty() {
+                format!("all {required} required suites are present")
+            } else {
+                format!("required suites missing: {}", missing.join(", "))
+            };
+
+            rules.push(RuleResult {
+                rule: Rule::RequiredSuites,
+                passed: missing.is_empty(),
+                actual: (required - missing.len()) as f64,
+                limit: required as f64,
+                message,
+            });
+        }
+        if let Some(min) = self.min_tests {
+            rules.push(RuleResult {
+                rule: Rule::MinTests,
+                passed: tests >= min,
+                actual: tests as f64,
+                limit: min as f64,
+                message: format!("{tests} tests ran, at least {min} required"),
+            });
+        }
+
+        Verdict {
+            passed: rules.iter().all(|r| r.passed),
+            rules,
+        }
+    }
+}
+
+impl FromStr for GateConfig {
+    type Err = Error;
+
+    /// Deserialize a GateConfig from a string of JSON text
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_slice(s.as_bytes())
+    }
+}
+
+/// A threshold of [`GateConfig`]
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum Rule {
+    MaxFailed,
+    MaxFailureRate,
+    MaxSkippedRatio,
+    MaxDuration,
+    RequiredSuites,
+    MinTests,
+}
+
+/// The outcome of a single rule
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleResult {
+    pub rule: Rule,
+    pub passed: bool,
+    /// Measured value: a count, a ratio, milliseconds or the number of required suites present
+    pub actual: f64,
+    /// Configured threshold, in the unit of `actual`
+    pub limit: f64,
+    pub message: String,
+}
+
+impl RuleResult {
+    fn at_most(rule: Rule, actual: f64, limit: f64, message: String) -> Self {
+        Self {
+            rule,
+            passed: actual <= limit,
+            actual,
+            limit,
+            message,
+        }
+    }
+}
+
+/// The outcome of every configured rule, in [`Rule`] order
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Verdict {
+    /// True if every rule passed, including when none are configured
+    pub passed: bool,
+    pub rules: Vec<RuleResult>,
+}
+
+impl Verdict {
+    /// Iterates over the rules that failed
+    pub fn failures(&self) -> impl Iterator<Item = &RuleResult> {
+        self.rules.iter().filter(|r| !r.passed)
+    }
+
+    /// Returns the process exit code: [`EXIT_PASSED`] or [`EXIT_FAILED`]
+    pub fn exit_code(&self) -> i32 {
+        if self.passed {
+            EXIT_PASSED
+        } else {
+            EXIT_FAILED
+        }
+    }
+}
+
+impl Results {
+    /// Checks the Results against the thresholds of a [`GateConfig`].
+    /// Unlike [`Results::all_passed`], tolerates failed or skipped tests within the thresholds.
+    pub fn evaluate_gates(&self, config: &GateConfig) -> Verdict {
+        config.evaluate(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{results::ResultsBuilder, test::Test, tool::Tool};
+
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn results() -> Results {
+        let mut builder = ResultsBuilder::new(Tool::new("ctrf-rs", None));
+        for (t, status) in [
+            Status::Passed,
+            Status::Passed,
+            Status::Passed,
+            Status::Skipped,
+            Status::Failed,
+        ]
+        .iter()
+        .enumerate()
+        {
+            let mut test = Test::new(format!("test{t}"), *status, Duration::from_millis(10));
+            test.suite = Some(String::from(if t < 3 { "unit" } else { "api" }));
+            builder.add_test(test);
+        }
+
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        builder.build(start, start + Duration::from_secs(90))
+    }
+
+    #[test]
+    fn all_rules() -> Result<()> {
+        let config: GateConfig = r#"{
+            "maxFailed": 1,
+            "max_failure_rate": 0.1,
+            "maxSkippedRatio": 0.25,
+            "maxDuration": 60000,
+            "requiredSuites": ["unit", "api", "e2e"],
+            "minTests": 5
+        }"#
+        .parse()?;
+
+        let verdict = results().evaluate_gates(&config);
+
+        assert!(!verdict.passed);
+        assert_eq!(verdict.exit_code(), EXIT_FAILED);
+        let failures: Vec<Rule> = verdict.failures().map(|r| r.rule).collect();
+        assert_eq!(
+            failures,
+            [
+                Rule::MaxFailureRate,
+                Rule::MaxDuration,
+                Rule::RequiredSuites
+            ]
+        );
+        assert_eq!(verdict.rules[1].actual, 0.2);
+        assert_eq!(verdict.rules[4].message, "required suites missing: e2e");
+
+        Ok(())
+    }
+
+    #[test]
+    fn tolerates_skipped_tests() -> Result<()> {
+        let results = results();
+        let config = GateConfig {
+            max_failed: Some(1),
+            max_skipped_ratio: Some(0.5),
+            ..GateConfig::default()
+        };
+
+        assert!(!results.all_passed());
+        assert_eq!(results.evaluate_gates(&config).exit_code(), EXIT_PASSED);
+        assert!(results.evaluate_gates(&GateConfig::default()).passed);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_keys() {
+        let error = GateConfig::from_str(r#"{"maxFailures": 0}"#).unwrap_err();
+
+        assert!(matches!(error, Error::Schema { .. }));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml() -> Result<()> {
+        let config = GateConfig::from_toml_str(
+            "max_failed = 0\nminTests = 10\nrequired_suites = [\"unit\"]\n",
+        )?;
+
+        assert_eq!(config.max_failed, Some(0));
+        assert_eq!(config.min_tests, Some(10));
+        assert_eq!(config.required_suites, ["unit"]);
+        assert!(matches!(
+            GateConfig::from_toml_str("max_failed = -1"),
+            Err(Error::Encoding { format: "TOML", .. })
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{
+    error::Result,
+    event::{Event, EventFold, EventWriter},
+    report::Report,
+    test::Test,
+    tool::Tool,
+};
+
+use std::{
+    fs::{self, File},
+    io::BufReader,
+    path::Path,
+};
+
+/// Message given to a test that was running when the journaled process died
+pub const CRASH_MESSAGE: &str =
+    "test did not finish: the process terminated while it was running (recovered from journal)";
+/// Raw status given to a test that was running when the journaled process died
+pub const CRASH_RAW_STATUS: &str = "crashed";
+
+/// Appends progress to a sidecar file as tests run, so that a report can be rebuilt with
+/// [`Journal::recover`] even if the process dies mid-run.
+/// The journal is an [`Event`] stream, so it can also be tailed while the run is in progress.
+pub struct Journal {
+    writer: EventWriter<File>,
+}
+
+impl Journal {
+    /// Creates (or truncates) the journal file and records the start of the run
+    pub fn create(path: impl AsRef<Path>, tool: &Tool) -> Result<Self> {
+        let mut writer = EventWriter::new(File::create(path)?);
+        writer.write(&Event::run_started(tool, None))?;
+
+        Ok(Self { writer })
+    }
+
+    /// Records that a test is about to run
+    pub fn test_started(&mut self, name: &str, suite: Option<&str>) -> Result<()> {
+        self.writer.write(&Event::test_started(name, suite))
+    }
+
+    /// Records a finished test
+    pub fn test_finished(&mut self, test: &Test) -> Result<()> {
+        self.writer.write(&Event::test_finished(test))
+    }
+
+    /// Rebuilds a Report from a journal, which may have been cut short by a crash.
+    /// Tests that started but never finished are reported as failed with [`CRASH_MESSAGE`].
+    /// The run is taken to stop at the journal's last modification.
+    pub fn recover(path: impl AsRef<Path>) -> Result<Report> {
+        let path = path.as_ref();
+        let modified = fs::metadata(path)?.modified()?;
+        let mut fold = EventFold::read(BufReader::new(File::open(path)?))?;
+        // File times are coarser than event times, so may fall just before the last event
+        let stop = modified.max(fold.latest());
+
+        fold.fail_running(CRASH_MESSAGE, CRASH_RAW_STATUS, stop);
+        fold.report(stop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test::Status;
+
+    use std::{fs::OpenOptions, io::Write, time::Duration};
+
+    use uuid::Uuid;
+
+    #[test]
+    fn recover_after_crash() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("ctrf-rs-journal-{}.ndjson", Uuid::new_v4()));
+
+        let mut journal = Journal::create(&path, &Tool::new("ctrf-rs", None))?;
+        journal.test_started("first", None)?;
+        journal.test_finished(&Test::new(
+            String::from("first"),
+            Status::Passed,
+            Duration::from_millis(3),
+        ))?;
+        journal.test_started("second", Some("suite"))?;
+        drop(journal);
+        // Simulate a torn write of the final line
+        OpenOptions::new()
+            .append(true)
+            .open(&path)?
+            .write_all(br#"{"event":"testFinished","test":{"na"#)?;
+
+        let report = Journal::recover(&path)?;
+        let tests = report.results().tests();
+
+        report.validate()?;
+        assert_eq!(tests.len(), 2);
+        assert_eq!(tests[0].name(), "first");
+        assert_eq!(tests[0].status(), Status::Passed);
+        assert_eq!(tests[1].name(), "second");
+        assert_eq!(tests[1].suite().as_deref(), Some("suite"));
+        assert_eq!(tests[1].status(), Status::Failed);
+        assert_eq!(tests[1].message.as_deref(), Some(CRASH_MESSAGE));
+
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn recover_corrupt_journal() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("ctrf-rs-journal-{}.ndjson", Uuid::new_v4()));
+        fs::write(
+            &path,
+            "garbage\n{\"event\":\"testStarted\",\"name\":\"t\",\"start\":0}\n",
+        )?;
+
+        let result = Journal::recover(&path);
+
+        assert_eq!(result.unwrap_err().path(), Some("$[0]"));
+
+        fs::remove_file(path)?;
+        Ok(())
+    }
+}
@@ -1,7 +1,35 @@
+pub mod build;
+#[cfg(feature = "bundle")]
+pub mod bundle;
+pub mod canonical;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub mod compression;
+#[cfg(not(any(feature = "gzip", feature = "zstd")))]
+mod compression;
+pub mod convert;
+#[cfg(feature = "preserve_order")]
+pub mod document;
+#[cfg(any(feature = "yaml", feature = "cbor", feature = "msgpack"))]
+mod encoding;
 pub mod environment;
+pub mod error;
+pub mod event;
 pub mod extra;
+pub mod gate;
+pub mod journal;
+pub mod owners;
+mod pattern;
+pub mod quarantine;
+pub mod reader;
+pub mod recorder;
+#[cfg(feature = "redact")]
+pub mod redact;
 pub mod report;
+pub mod rerun;
 pub mod results;
+pub mod stats;
+pub mod suite_tree;
 pub mod summary;
 pub mod test;
 pub mod tool;
+pub mod writer;
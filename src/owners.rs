@@ -0,0 +1,453 @@
+use crate::{
+    canonical::normalize_path,
+    error::{Error, Result},
+    extra::{Extra, ExtraExtension},
+    pattern::path_match,
+    results::Results,
+    test::{Status, Test},
+};
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Read},
+    path::Path,
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Locations searched by [`CodeOwners::discover`], in order
+pub const CODEOWNERS_PATHS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// Owners of a test, recorded in its `extra` by [`Results::assign_owners`]
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Owners(pub Vec<String>);
+
+impl ExtraExtension for Owners {
+    const KEY: &'static str = "owners";
+}
+
+/// A rule of a CODEOWNERS file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnerRule {
+    /// The pattern as written
+    pub pattern: String,
+    /// Empty if the rule removes ownership
+    pub owners: Vec<String>,
+    segments: Vec<String>,
+    contents: bool,
+}
+
+impl OwnerRule {
+    fn new(pattern: &str, owners: Vec<String>) -> Result<Self> {
+        if pattern.starts_with('!') {
+            return Err(Error::Pattern {
+                pattern: String::from(pattern),
+                message: String::from("negated patterns are not supported by CODEOWNERS"),
+            });
+        }
+
+        // As in .gitignore, a pattern without a `/` except a trailing one matches at any depth,
+        // and one naming a directory matches everything below it
+        let trimmed = pattern.trim_end_matches('/');
+        let anchored = trimmed.contains('/');
+        let mut segments: Vec<String> = trimmed
+            .trim_start_matches('/')
+            .split('/')
+            .map(String::from)
+            .collect();
+        if !anchored {
+            segments.insert(0, String::from("**"));
+        }
+        // Unlike .gitignore, `docs/*` does not match files in subdirectories of `docs`
+        let last = segments.last().map(String::as_str).unwrap_or_default();
+        let contents = last == "**" || !last.contains('*');
+
+        Ok(Self {
+            pattern: String::from(pattern),
+            owners,
+            segments,
+            contents,
+        })
+    }
+
+    /// Checks whether the rule covers a `/`-separated path relative to the repository root
+    pub fn matches(&self, path: &str) -> bool {
+        let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let segments: Vec<&str> = self.segments.iter().map(String::as_str).collect();
+
+        let file = !self.pattern.ends_with('/') && path_match(&segments, &path);
+        let below = self.contents && (1..path.len()).any(|len| path_match(&segments, &path[..len]));
+
+        file || below
+    }
+}
+
+/// Ownership rules parsed from a CODEOWNERS file.  The last rule matching a path applies.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CodeOwners {
+    rules: Vec<OwnerRule>,
+}
+
+impl CodeOwners {
+    /// Reads the CODEOWNERS file of a repository from the first of [`CODEOWNERS_PATHS`] present
+    /// under `root`, returning None if there is none
+    pub fn discover(root: impl AsRef<Path>) -> Result<Option<Self>> {
+        for path in CODEOWNERS_PATHS {
+            match fs::read_to_string(root.as_ref().join(path)) {
+                Ok(text) => return text.parse().map(Some),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Parses a CODEOWNERS file from an I/O stream
+    pub fn from_reader(mut r: impl Read) -> Result<Self> {
+        let mut text = String::new();
+        r.read_to_string(&mut text)?;
+
+        text.parse()
+    }
+
+    /// Borrows the rules, in file order
+    pub fn rules(&self) -> &[OwnerRule] {
+        &self.rules
+    }
+
+    /// Returns the owners of a path relative to the repository root, empty if it has none
+    pub fn owners_of(&self, path: &str) -> &[String] {
+        let path = normalize_path(path);
+
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(&path))
+            .map_or(&[], |rule| &rule.owners)
+    }
+}
+
+impl FromStr for CodeOwners {
+    type Err = Error;
+
+    /// Parses the text of a CODEOWNERS file.  Comments are skipped, and `\#` and `\ ` stand for
+    /// a literal `#` or space in a pattern.  The owners following a GitLab `[Section]` header
+    /// are given to the section's rules without owners; owners of several sections matching a
+    /// path are not combined, the last matching rule of the file applies.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut rules = vec![];
+        let mut section_owners = vec![];
+
+        for line in s.lines() {
+            let line = strip_comment(line).trim();
+            if line.starts_with('[') || line.starts_with("^[") {
+                section_owners = section_header_owners(line).unwrap_or_default();
+                continue;
+            }
+
+            let mut fields = split_rule(line).into_iter();
+            let pattern = match fields.next() {
+                Some(pattern) => pattern,
+                None => continue,
+            };
+            let mut owners: Vec<String> = fields.collect();
+            if owners.is_empty() {
+                owners = section_owners.clone();
+            }
+            rules.push(OwnerRule::new(&pattern, owners)?);
+        }
+
+        Ok(Self { rules })
+    }
+}
+
+/// Cuts a line at the first `#` not escaped as `\#`
+fn strip_comment(line: &str) -> &str {
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '#' if !escaped => return &line[..i],
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+
+    line
+}
+
+/// Splits a rule at whitespace, resolving the `\#` and `\ ` escapes
+fn split_rule(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped @ ('#' | ' ')) => field.push(escaped),
+                Some(other) => {
+                    field.push('\\');
+                    field.push(other);
+                }
+                None => field.push('\\'),
+            },
+            c if c.is_whitespace() => {
+                if !field.is_empty() {
+                    fields.push(std::mem::take(&mut field));
+                }
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() {
+        fields.push(field);
+    }
+
+    fields
+}
+
+/// Returns the default owners of a GitLab section header such as `[Name] @owner`,
+/// `^[Name]` or `[Name][2] @owner`, or None if the header is malformed
+fn section_header_owners(line: &str) -> Option<Vec<String>> {
+    let header = line.strip_prefix('^').unwrap_or(line).strip_prefix('[')?;
+    let (_, mut rest) = header.split_once(']')?;
+    if let Some(approvals) = rest.strip_prefix('[') {
+        rest = approvals.split_once(']')?.1;
+    }
+
+    Some(rest.split_whitespace().map(String::from).collect())
+}
+
+/// Test counts and duration of an owner's tests
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnerSummary {
+    /// None for the tests without owners
+    pub owner: Option<String>,
+    pub tests: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub pending: usize,
+    pub skipped: usize,
+    pub other: usize,
+    /// Sum of the test durations in milliseconds
+    pub duration: u64,
+}
+
+impl OwnerSummary {
+    fn add(&mut self, test: &Test) {
+        self.tests += 1;
+        *match test.status() {
+            Status::Passed => &mut self.passed,
+            Status::Failed => &mut self.failed,
+            Status::Pending => &mut self.pending,
+            Status::Skipped => &mut self.skipped,
+            Status::Other => &mut self.other,
+        } += 1;
+        self.duration += test.duration().as_millis() as u64;
+    }
+}
+
+impl Results {
+    /// Records the owners of each test's `filepath` under [`Owners::KEY`], replacing any recorded
+    /// before.  Filepaths must be relative to the repository root.
+    /// Returns the number of tests given owners.
+    pub fn assign_owners(&mut self, code_owners: &CodeOwners) -> Result<usize> {
+        let mut count = 0;
+
+        for test in self.tests_mut() {
+            let owners = match &test.filepath {
+                Some(path) => code_owners.owners_of(&path.to_string_lossy()),
+                None => &[],
+            };
+            if owners.is_empty() {
+                test.remove_extension::<Owners>()?;
+            } else {
+                test.set_extension(&Owners(owners.to_vec()))?;
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Summarizes the tests of each owner recorded by [`Results::assign_owners`], ordered by
+    /// owner with the unowned tests last.  A test with several owners counts for each of them.
+    pub fn owner_summaries(&self) -> Result<Vec<OwnerSummary>> {
+        let mut summaries: BTreeMap<Option<String>, OwnerSummary> = BTreeMap::new();
+
+        for test in self.tests() {
+            for owner in test_owners(test)? {
+                summaries
+                    .entry(owner.clone())
+                    .or_insert_with(|| OwnerSummary {
+                        owner,
+                        ..OwnerSummary::default()
+                    })
+                    .add(test);
+            }
+        }
+
+        Ok(unowned_last(summaries)
+            .into_iter()
+            .map(|(_, summary)| summary)
+            .collect())
+    }
+
+    /// Groups the failed tests by owner, ordered as [`Results::owner_summaries`], so each owner
+    /// can be notified of their failures
+    pub fn failed_by_owner(&self) -> Result<Vec<(Option<String>, Vec<&Test>)>> {
+        let mut groups: BTreeMap<Option<String>, Vec<&Test>> = BTreeMap::new();
+
+        for test in self.tests() {
+            if test.status() != Status::Failed {
+                continue;
+            }
+            for owner in test_owners(test)? {
+                groups.entry(owner).or_default().push(test);
+            }
+        }
+
+        Ok(unowned_last(groups))
+    }
+}
+
+/// The owners of a test, a single None if it has none
+fn test_owners(test: &Test) -> Result<Vec<Option<String>>> {
+    let owners = test.get_extension::<Owners>()?.unwrap_or_default().0;
+
+    Ok(if owners.is_empty() {
+        vec![None]
+    } else {
+        owners.into_iter().map(Some).collect()
+    })
+}
+
+/// Orders the entries by owner, with the unowned entry last
+fn unowned_last<V>(map: BTreeMap<Option<String>, V>) -> Vec<(Option<String>, V)> {
+    let mut entries: Vec<(Option<String>, V)> = map.into_iter().collect();
+    // Stable, so owners stay in order
+    entries.sort_by_key(|(owner, _)| owner.is_none());
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{results::ResultsBuilder, tool::Tool};
+
+    use std::{
+        path::PathBuf,
+        time::{Duration, SystemTime},
+    };
+
+    const CODEOWNERS: &str = "
+# Default owners
+*       @org/core
+*.md    @org/docs   # documentation
+/crates/net/ @alice @org/net
+docs/*  @bob
+[Generated]
+crates/net/src/gen.rs
+apps/   @carol
+";
+
+    #[test]
+    fn matching() -> Result<()> {
+        let owners: CodeOwners = CODEOWNERS.parse()?;
+
+        assert_eq!(owners.owners_of("src/lib.rs"), ["@org/core"]);
+        assert_eq!(
+            owners.owners_of("crates/net/README.md"),
+            ["@alice", "@org/net"]
+        );
+        assert_eq!(
+            owners.owners_of(".\\crates\\net\\src\\tcp.rs"),
+            ["@alice", "@org/net"]
+        );
+        assert!(owners.owners_of("crates/net/src/gen.rs").is_empty());
+        assert_eq!(owners.owners_of("docs/guide.md"), ["@bob"]);
+        assert_eq!(owners.owners_of("docs/api/index.md"), ["@org/docs"]);
+        assert_eq!(owners.owners_of("tools/apps/x.rs"), ["@carol"]);
+        assert_eq!(owners.owners_of("tools/apps"), ["@org/core"]);
+        assert!(matches!(
+            "!secret.rs @x".parse::<CodeOwners>(),
+            Err(Error::Pattern { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn summaries() -> Result<()> {
+        let mut builder = ResultsBuilder::new(Tool::new("ctrf-rs", None));
+        for (name, status, filepath) in [
+            ("connect", Status::Failed, Some("crates/net/src/tcp.rs")),
+            ("send", Status::Passed, Some("crates/net/tests/api.rs")),
+            ("parse", Status::Failed, Some("src/lib.rs")),
+            ("generated", Status::Failed, Some("crates/net/src/gen.rs")),
+            ("adhoc", Status::Skipped, None),
+        ] {
+            let mut test = Test::new(String::from(name), status, Duration::from_millis(10));
+            test.filepath = filepath.map(PathBuf::from);
+            builder.add_test(test);
+        }
+        let time = SystemTime::now();
+        let mut results = builder.build(time, time);
+
+        assert_eq!(results.assign_owners(&CODEOWNERS.parse()?)?, 3);
+        assert_eq!(
+            results.tests()[0].get_extension::<Owners>()?,
+            Some(Owners(vec![
+                String::from("@alice"),
+                String::from("@org/net")
+            ]))
+        );
+
+        let summaries = results.owner_summaries()?;
+        let owners: Vec<_> = summaries.iter().map(|s| s.owner.as_deref()).collect();
+        assert_eq!(
+            owners,
+            [Some("@alice"), Some("@org/core"), Some("@org/net"), None]
+        );
+        assert_eq!((summaries[0].tests, summaries[0].failed), (2, 1));
+        assert_eq!(summaries[0].duration, 20);
+        assert_eq!((summaries[3].tests, summaries[3].skipped), (2, 1));
+
+        let failed = results.failed_by_owner()?;
+        assert_eq!(failed.len(), 4);
+        assert_eq!(failed[1].0.as_deref(), Some("@org/core"));
+        assert_eq!(failed[1].1[0].name(), "parse");
+        assert_eq!(failed[3].1[0].name(), "generated");
+
+        Ok(())
+    }
+
+    #[test]
+    fn sections_and_escapes() -> Result<()> {
+        let owners: CodeOwners = "
+[Docs][2] @org/docs
+docs/
+docs/api/ @alice
+\\#notes.md @bob # notes
+my\\ file.rs @carol
+^[Generated]
+docs/gen/
+"
+        .parse()?;
+
+        assert_eq!(owners.owners_of("docs/guide.md"), ["@org/docs"]);
+        assert_eq!(owners.owners_of("docs/api/index.md"), ["@alice"]);
+        assert_eq!(owners.owners_of("#notes.md"), ["@bob"]);
+        assert_eq!(owners.owners_of("src/my file.rs"), ["@carol"]);
+        assert!(owners.owners_of("docs/gen/index.md").is_empty());
+
+        Ok(())
+    }
+}
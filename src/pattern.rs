@@ -0,0 +1,69 @@
+//! Wildcard matching shared by the quarantine list and CODEOWNERS rules
+
+/// Matches text against a pattern where `*` matches any text and `?` any single character
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it was tried at
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                backtrack = Some((p, t));
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, from)) => {
+                    p = star;
+                    t = from + 1;
+                    backtrack = Some((star, from + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Matches `/`-separated path segments against pattern segments, where a `**` segment matches
+/// any number of path segments and other segments are matched with [`glob_match`]
+pub(crate) fn path_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| path_match(rest, &path[skip..])),
+        Some((segment, rest)) => match path.split_first() {
+            Some((first, path)) => glob_match(segment, first) && path_match(rest, path),
+            None => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs() {
+        assert!(glob_match("ui::*::render?", "ui::menu::render2"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("ui::*::render?", "ui::menu::render"));
+        assert!(!glob_match("a*b", "aXbY"));
+    }
+
+    #[test]
+    fn paths() {
+        assert!(path_match(&["**", "*.rs"], &["src", "net", "tcp.rs"]));
+        assert!(path_match(&["**", "*.rs"], &["lib.rs"]));
+        assert!(path_match(&["src", "**"], &["src", "a", "b"]));
+        assert!(!path_match(&["src", "*"], &["src", "a", "b"]));
+        assert!(!path_match(&["docs", "**"], &["src", "docs", "a"]));
+    }
+}
@@ -0,0 +1,372 @@
+use crate::{
+    error::{Error, Result},
+    extra::{Extra, ExtraExtension},
+    pattern::glob_match,
+    results::Results,
+    test::Status,
+};
+
+use std::{
+    io::Read,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Key of the summary's `extra` map counting the quarantined tests, see
+/// [`Results::apply_quarantine`]
+pub const QUARANTINED_KEY: &str = "quarantined";
+
+/// Raw status recorded for a quarantined failure that had none
+pub const QUARANTINED_RAW_STATUS: &str = "failed";
+
+/// A known-flaky test, matched by exact name or by a glob pattern, optionally within a suite
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantineEntry {
+    /// Exact test name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Test name pattern, where `*` matches any text and `?` any single character
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Exact suite, if the entry is restricted to one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suite: Option<String>,
+    /// Last day, as `YYYY-MM-DD` in UTC, on which the entry applies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    /// Issue tracking the fix
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticket: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl QuarantineEntry {
+    /// Checks whether the entry covers a test of the given suite and name
+    pub fn matches(&self, suite: Option<&str>, name: &str) -> bool {
+        if self.suite.is_some() && self.suite.as_deref() != suite {
+            return false;
+        }
+
+        match (&self.name, &self.pattern) {
+            (Some(exact), _) => exact == name,
+            (None, Some(pattern)) => glob_match(pattern, name),
+            (None, None) => false,
+        }
+    }
+
+    /// Checks whether the entry no longer applies on the day of `now`
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        let today = now
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| (d.as_secs() / 86_400) as i64);
+
+        self.expires
+            .as_deref()
+            .and_then(parse_date)
+            .map_or(false, |expires| today > expires)
+    }
+}
+
+/// Recorded in a quarantined test's `extra`
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Quarantined {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticket: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl ExtraExtension for Quarantined {
+    const KEY: &'static str = "quarantine";
+}
+
+/// A list of known-flaky tests whose failures are downgraded to [`Status::Other`], read from a
+/// JSON array of [`QuarantineEntry`] objects
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Quarantine {
+    entries: Vec<QuarantineEntry>,
+}
+
+impl Quarantine {
+    /// Creates a Quarantine from entries, checking each of them
+    pub fn new(entries: Vec<QuarantineEntry>) -> Result<Self> {
+        let quarantine = Self { entries };
+        quarantine.check()?;
+
+        Ok(quarantine)
+    }
+
+    /// Deserialize a Quarantine from bytes of JSON text
+    pub fn from_slice(s: &[u8]) -> Result<Self> {
+        let mut deserializer = serde_json::Deserializer::from_slice(s);
+        let quarantine: Self = serde_path_to_error::deserialize(&mut deserializer)
+            .map_err(|e| Error::tracked("$", e))?;
+        deserializer.end()?;
+        quarantine.check()?;
+
+        Ok(quarantine)
+    }
+
+    /// Deserialize a Quarantine from an I/O stream of JSON text
+    pub fn from_reader(mut r: impl Read) -> Result<Self> {
+        let mut buf = vec![];
+        r.read_to_end(&mut buf)?;
+
+        Self::from_slice(&buf)
+    }
+
+    /// Borrows the entries
+    pub fn entries(&self) -> &[QuarantineEntry] {
+        &self.entries
+    }
+
+    /// Returns the entries that no longer apply on the day of `now`, so they can be reviewed
+    pub fn expired(&self, now: SystemTime) -> Vec<&QuarantineEntry> {
+        self.entries.iter().filter(|e| e.is_expired(now)).collect()
+    }
+
+    /// Returns the first entry applying on the day of `now` that covers the test
+    pub fn find(
+        &self,
+        suite: Option<&str>,
+        name: &str,
+        now: SystemTime,
+    ) -> Option<&QuarantineEntry> {
+        self.entries
+            .iter()
+            .find(|e| !e.is_expired(now) && e.matches(suite, name))
+    }
+
+    fn check(&self) -> Result<()> {
+        for (index, entry) in self.entries.iter().enumerate() {
+            let invalid = |field: &str, message: &str| Error::Consistency {
+                path: format!("$[{index}].{field}"),
+                message: String::from(message),
+            };
+
+            if entry.name.is_some() == entry.pattern.is_some() {
+                return Err(invalid(
+                    "name",
+                    "exactly one of `name` and `pattern` is required",
+                ));
+            }
+            if let Some(expires) = &entry.expires {
+                if parse_date(expires).is_none() {
+                    return Err(invalid("expires", "expected a date as YYYY-MM-DD"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Quarantine {
+    type Err = Error;
+
+    /// Deserialize a Quarantine from a string of JSON text
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_slice(s.as_bytes())
+    }
+}
+
+impl Results {
+    /// Rewrites failed tests covered by an unexpired quarantine entry to [`Status::Other`],
+    /// keeping `"failed"` as their `raw_status` unless one is present and recording the entry's
+    /// ticket under [`Quarantined::KEY`].  The summary is recounted, with the number of
+    /// quarantined tests under [`QUARANTINED_KEY`].  Returns the number of tests quarantined by
+    /// this call.
+    pub fn apply_quarantine(&mut self, quarantine: &Quarantine, now: SystemTime) -> Result<usize> {
+        let mut count = 0;
+
+        for test in self.tests_mut() {
+            if test.status() != Status::Failed {
+                continue;
+            }
+            let entry = match quarantine.find(test.suite().as_deref(), test.name(), now) {
+                Some(entry) => entry,
+                None => continue,
+            };
+
+            test.set_status(Status::Other);
+            if test.raw_status.is_none() {
+                test.raw_status = Some(String::from(QUARANTINED_RAW_STATUS));
+            }
+            test.set_extension(&Quarantined {
+                ticket: entry.ticket.clone(),
+                expires: entry.expires.clone(),
+                reason: entry.reason.clone(),
+            })?;
+            count += 1;
+        }
+
+        let quarantined = self
+            .tests()
+            .iter()
+            .filter(|t| t.status() == Status::Other && t.extra_map().contains_key(Quarantined::KEY))
+            .count();
+        self.recount();
+        self.summary_mut()
+            .set_extra(QUARANTINED_KEY, &quarantined)?;
+
+        Ok(count)
+    }
+
+    /// Checks that every test passed, except for up to the number of tests with status `other`
+    /// recorded under [`QUARANTINED_KEY`] by [`Results::apply_quarantine`]; a missing or invalid
+    /// count tolerates none
+    pub fn all_passed_except_quarantined(&self) -> bool {
+        let summary = self.summary();
+        let quarantined = summary
+            .get_extra::<usize>(QUARANTINED_KEY)
+            .ok()
+            .flatten()
+            .unwrap_or(0);
+
+        summary.count(Status::Passed) + quarantined.min(summary.count(Status::Other))
+            == summary.tests()
+    }
+}
+
+/// Parses a `YYYY-MM-DD` date into days since the Unix epoch
+fn parse_date(date: &str) -> Option<i64> {
+    let mut parts = date.splitn(3, '-');
+    let mut part = |len: usize| {
+        parts
+            .next()
+            .filter(|p| p.len() == len && p.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|p| p.parse::<i64>().ok())
+    };
+    let (year, month, day) = (part(4)?, part(2)?, part(2)?);
+
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    if day < 1 || day > days_in_month {
+        return None;
+    }
+
+    // Days from civil date, counting years from March so the leap day comes last
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    Some(era * 146_097 + day_of_era - 719_468)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{results::ResultsBuilder, test::Test, tool::Tool};
+
+    use std::time::Duration;
+
+    const LIST: &str = r#"[
+        {"name": "net::connect", "ticket": "NET-1", "expires": "2026-03-31"},
+        {"pattern": "ui::*::render?", "suite": "ui", "ticket": "UI-7"},
+        {"name": "old", "expires": "2020-01-01"}
+    ]"#;
+
+    fn date(days: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(days * 86_400 + 3_600)
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("2000-03-01"), Some(11_017));
+        assert_eq!(parse_date("2024-02-29"), Some(19_782));
+        assert_eq!(parse_date("2023-02-29"), None);
+        assert_eq!(parse_date("2024-1-01"), None);
+    }
+
+    #[test]
+    fn invalid_entries() {
+        let error =
+            Quarantine::from_str(r#"[{"name": "a"}, {"name": "b", "pattern": "*"}]"#).unwrap_err();
+        assert_eq!(error.path(), Some("$[1].name"));
+
+        let error = Quarantine::from_str(r#"[{"name": "a", "expires": "soon"}]"#).unwrap_err();
+        assert_eq!(error.path(), Some("$[0].expires"));
+    }
+
+    #[test]
+    fn apply() -> Result<()> {
+        let quarantine = Quarantine::from_str(LIST)?;
+        let mut builder = ResultsBuilder::new(Tool::new("ctrf-rs", None));
+        for (name, suite, status) in [
+            ("net::connect", None, Status::Failed),
+            ("ui::menu::render2", Some("ui"), Status::Failed),
+            ("ui::menu::render3", Some("api"), Status::Failed),
+            ("old", None, Status::Passed),
+        ] {
+            let mut test = Test::new(String::from(name), status, Duration::from_millis(1));
+            test.suite = suite.map(String::from);
+            builder.add_test(test);
+        }
+        let time = SystemTime::now();
+        let mut results = builder.build(time, time);
+        let now = date(parse_date("2026-03-31").unwrap() as u64);
+        assert!(!results.all_passed_except_quarantined());
+
+        assert_eq!(results.apply_quarantine(&quarantine, now)?, 2);
+        assert!(results.validate().is_ok());
+        assert_eq!(results.summary().count(Status::Other), 2);
+        assert_eq!(results.summary().count(Status::Failed), 1);
+        assert!(!results.all_passed());
+        assert!(!results.all_passed_except_quarantined());
+        let test = &results.tests()[0];
+        assert_eq!(test.raw_status.as_deref(), Some(QUARANTINED_RAW_STATUS));
+        assert_eq!(
+            test.get_extension::<Quarantined>()?
+                .unwrap()
+                .ticket
+                .as_deref(),
+            Some("NET-1")
+        );
+
+        results.tests_mut()[2].set_status(Status::Passed);
+        results.apply_quarantine(&quarantine, now)?;
+        let quarantined = results.summary().get_extra::<usize>(QUARANTINED_KEY)?;
+        assert_eq!(quarantined, Some(2));
+        assert!(!results.all_passed());
+        assert!(results.all_passed_except_quarantined());
+
+        results.summary_mut().set_extra(QUARANTINED_KEY, &1)?;
+        assert!(!results.all_passed_except_quarantined());
+        results.summary_mut().set_extra(QUARANTINED_KEY, "two")?;
+        assert!(!results.all_passed_except_quarantined());
+
+        Ok(())
+    }
+
+    #[test]
+    fn expiry() -> Result<()> {
+        let quarantine = Quarantine::from_str(LIST)?;
+        let day = parse_date("2026-04-01").unwrap() as u64;
+
+        assert!(quarantine.find(None, "net::connect", date(day)).is_none());
+        assert!(quarantine
+            .find(None, "net::connect", date(day - 1))
+            .is_some());
+        assert_eq!(quarantine.expired(date(day)).len(), 2);
+
+        Ok(())
+    }
+}
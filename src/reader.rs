@@ -0,0 +1,432 @@
+use crate::{
+    environment::Environment,
+    error::{Error, Result},
+    report::{is_supported_spec_version, REPORT_FORMAT},
+    summary::Summary,
+    test::Test,
+    tool::Tool,
+};
+
+use std::io::{BufRead, BufReader, Read};
+
+use semver::Version;
+use serde::{de::DeserializeOwned, de::Error as _};
+
+/// Reads a CTRF report incrementally, yielding one Test at a time from `results.tests`,
+/// so that arbitrarily large reports can be processed in constant memory.
+///
+/// The remaining elements are captured as they are passed.  The tool and summary normally precede
+/// the tests and are available once the reader is created; the environment normally follows them
+/// and is only available once the iterator is exhausted.
+/// `reportFormat` and `specVersion` are checked where they appear; if either is missing, the
+/// iterator ends with an error.
+pub struct ReportReader<R: Read> {
+    reader: BufReader<R>,
+    state: State,
+    /// Whether `reportFormat` and `specVersion` have been read
+    header: (bool, bool),
+    tool: Option<Tool>,
+    summary: Option<Summary>,
+    environment: Option<Environment>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    /// Inside `results.tests`, having yielded the given number of tests
+    Tests(usize),
+    Done,
+}
+
+impl<R: Read> ReportReader<R> {
+    /// Reads the document up to the first Test
+    pub fn new(reader: R) -> Result<Self> {
+        let mut report_reader = Self {
+            reader: BufReader::new(reader),
+            state: State::Done,
+            header: (false, false),
+            tool: None,
+            summary: None,
+            environment: None,
+        };
+
+        report_reader.skip_whitespace()?;
+        report_reader.expect(b'{', "$")?;
+        if !report_reader.root_fields(true)? {
+            return Err(missing_field("$.results", "missing field `tests`"));
+        }
+        report_reader.state = State::Tests(0);
+
+        Ok(report_reader)
+    }
+
+    /// Borrows the Tool, if it has been read
+    pub fn tool(&self) -> Option<&Tool> {
+        self.tool.as_ref()
+    }
+
+    /// Borrows the Summary, if it has been read
+    pub fn summary(&self) -> Option<&Summary> {
+        self.summary.as_ref()
+    }
+
+    /// Borrows the Environment, if it has been read
+    pub fn environment(&self) -> Option<&Environment> {
+        self.environment.as_ref()
+    }
+
+    /// Reads the fields of the root object.
+    /// Returns true when stopping at the start of `results.tests`, false at the end of the object.
+    fn root_fields(&mut self, mut first: bool) -> Result<bool> {
+        while let Some(key) = self.next_key(first, "$")? {
+            first = false;
+
+            match key.as_str() {
+                "reportFormat" => {
+                    let format: String = self.value("$.reportFormat")?;
+                    if format != REPORT_FORMAT {
+                        return Err(Error::Format { found: format });
+                    }
+                    self.header.0 = true;
+                }
+                "specVersion" => {
+                    let version: Version = self.value("$.specVersion")?;
+                    if !is_supported_spec_version(&version) {
+                        return Err(Error::UnsupportedSpecVersion { found: version });
+                    }
+                    self.header.1 = true;
+                }
+                "results" => {
+                    self.skip_whitespace()?;
+                    self.expect(b'{', "$.results")?;
+                    if self.results_fields(true)? {
+                        return Ok(true);
+                    }
+                }
+                _ => {
+                    self.capture()?;
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Reads the fields of the `results` object.
+    /// Returns true when stopping at the start of `results.tests`, false at the end of the object.
+    fn results_fields(&mut self, mut first: bool) -> Result<bool> {
+        while let Some(key) = self.next_key(first, "$.results")? {
+            first = false;
+
+            match key.as_str() {
+                "tests" => {
+                    self.skip_whitespace()?;
+                    self.expect(b'[', "$.results.tests")?;
+                    return Ok(true);
+                }
+                "tool" => self.tool = Some(self.value("$.results.tool")?),
+                "summary" => self.summary = Some(self.value("$.results.summary")?),
+                "environment" => self.environment = Some(self.value("$.results.environment")?),
+                _ => {
+                    self.capture()?;
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Reads everything following `results.tests`
+    fn finish(&mut self) -> Result<()> {
+        if self.results_fields(false)? || self.root_fields(false)? {
+            return Err(syntax("$.results", "duplicate field `tests`"));
+        }
+        match self.header {
+            (false, _) => return Err(missing_field("$", "missing field `reportFormat`")),
+            (_, false) => return Err(missing_field("$", "missing field `specVersion`")),
+            _ => {}
+        }
+
+        self.skip_whitespace()?;
+        match self.peek()? {
+            None => Ok(()),
+            Some(_) => Err(syntax("$", "trailing characters")),
+        }
+    }
+
+    fn next_test(&mut self, index: usize) -> Result<Option<Test>> {
+        self.skip_whitespace()?;
+        if self.peek()? == Some(b']') {
+            self.bump();
+            self.state = State::Done;
+            self.finish()?;
+            return Ok(None);
+        }
+        if index > 0 {
+            self.expect(b',', "$.results.tests")?;
+        }
+
+        let path = format!("$.results.tests[{index}]");
+        let test = self.value(&path)?;
+        self.state = State::Tests(index + 1);
+
+        Ok(Some(test))
+    }
+
+    /// Reads the next key of an object, or returns None at its end
+    fn next_key(&mut self, first: bool, path: &str) -> Result<Option<String>> {
+        self.skip_whitespace()?;
+        if self.peek()? == Some(b'}') {
+            self.bump();
+            return Ok(None);
+        }
+        if !first {
+            self.expect(b',', path)?;
+        }
+
+        let key: String = self.value(path)?;
+        self.skip_whitespace()?;
+        self.expect(b':', path)?;
+
+        Ok(Some(key))
+    }
+
+    /// Reads and deserializes the next value
+    fn value<T: DeserializeOwned>(&mut self, path: &str) -> Result<T> {
+        let raw = self.capture()?;
+        let mut deserializer = serde_json::Deserializer::from_slice(&raw);
+
+        serde_path_to_error::deserialize(&mut deserializer).map_err(|e| Error::tracked(path, e))
+    }
+
+    /// Returns the raw bytes of the next value, without interpreting them
+    fn capture(&mut self) -> Result<Vec<u8>> {
+        self.skip_whitespace()?;
+
+        let mut raw = vec![];
+        let mut depth = 0_usize;
+        let mut in_string = false;
+        let mut escaped = false;
+
+        while let Some(b) = self.peek()? {
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if b == b'\\' {
+                    escaped = true;
+                } else if b == b'"' {
+                    in_string = false;
+                }
+            } else {
+                match b {
+                    b'"' => in_string = true,
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' if depth == 0 => break,
+                    b'}' | b']' => depth -= 1,
+                    b',' if depth == 0 => break,
+                    b if b.is_ascii_whitespace() && depth == 0 => break,
+                    _ => {}
+                }
+            }
+
+            raw.push(b);
+            self.bump();
+
+            if depth == 0 && !in_string && matches!(b, b'"' | b'}' | b']') {
+                break;
+            }
+        }
+
+        Ok(raw)
+    }
+
+    fn skip_whitespace(&mut self) -> Result<()> {
+        while let Some(b) = self.peek()? {
+            if !b.is_ascii_whitespace() {
+                break;
+            }
+            self.bump();
+        }
+
+        Ok(())
+    }
+
+    fn expect(&mut self, expected: u8, path: &str) -> Result<()> {
+        match self.peek()? {
+            Some(b) if b == expected => {
+                self.bump();
+                Ok(())
+            }
+            _ => Err(syntax(path, &format!("expected '{}'", expected as char))),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>> {
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+
+    fn bump(&mut self) {
+        self.reader.consume(1);
+    }
+}
+
+impl<R: Read> Iterator for ReportReader<R> {
+    type Item = Result<Test>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = match self.state {
+            State::Tests(index) => index,
+            State::Done => return None,
+        };
+
+        let result = self.next_test(index);
+        if result.is_err() {
+            self.state = State::Done;
+        }
+
+        result.transpose()
+    }
+}
+
+/// A Syntax error for a malformed stream
+fn syntax(path: &str, message: &str) -> Error {
+    Error::Syntax {
+        path: String::from(path),
+        source: serde_json::Error::custom(message),
+    }
+}
+
+/// A Schema error for a well-formed stream lacking a required field
+fn missing_field(path: &str, message: &str) -> Error {
+    Error::schema(String::from(path), serde_json::Error::custom(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{report::Report, results::ResultsBuilder, test::Status};
+
+    use std::time::{Duration, SystemTime};
+
+    fn report_json(count: usize) -> Vec<u8> {
+        let mut builder = ResultsBuilder::new(Tool::new("ctrf-rs", None));
+        for t in 0..count {
+            let status = if t % 3 == 0 {
+                Status::Failed
+            } else {
+                Status::Passed
+            };
+            let mut test = Test::new(
+                format!("test {t} \"quoted\" [x]"),
+                status,
+                Duration::from_millis(t as u64),
+            );
+            test.stdout = vec![String::from("{ unbalanced")];
+            builder.add_test(test);
+        }
+        let mut environment = Environment::new();
+        environment.branch_name = Some(String::from("main"));
+        builder.environment(Some(environment));
+
+        let time = SystemTime::now();
+        Report::new(None, None, None, builder.build(time, time))
+            .to_vec_pretty()
+            .unwrap()
+    }
+
+    #[test]
+    fn stream_tests() -> Result<()> {
+        let json = report_json(10);
+        let report = Report::from_slice(&json)?;
+
+        let mut reader = ReportReader::new(json.as_slice())?;
+        assert_eq!(reader.tool(), Some(report.results().tool()));
+        assert_eq!(reader.summary(), Some(report.results().summary()));
+        assert_eq!(reader.environment(), None);
+
+        let tests = reader.by_ref().collect::<Result<Vec<_>>>()?;
+        assert_eq!(tests, report.results().tests());
+        assert_eq!(reader.environment(), report.results().environment());
+
+        Ok(())
+    }
+
+    #[test]
+    fn stream_empty() -> Result<()> {
+        let json = report_json(0);
+
+        assert_eq!(ReportReader::new(json.as_slice())?.count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn stream_bad_test() {
+        let json = String::from_utf8(report_json(2)).unwrap().replacen(
+            r#""status": "passed""#,
+            r#""status": "bogus""#,
+            1,
+        );
+
+        let results: Vec<_> = ReportReader::new(json.as_bytes()).unwrap().collect();
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert_eq!(
+            results[1].as_ref().unwrap_err().path(),
+            Some("$.results.tests[1].status")
+        );
+    }
+
+    #[test]
+    fn stream_bad_format() {
+        let json = String::from_utf8(report_json(1))
+            .unwrap()
+            .replace("CTRF", "JUNIT");
+
+        assert!(matches!(
+            ReportReader::new(json.as_bytes()),
+            Err(Error::Format { .. })
+        ));
+    }
+
+    #[test]
+    fn stream_missing_header() {
+        let json = String::from_utf8(report_json(1)).unwrap();
+
+        for (field, line) in [
+            ("specVersion", r#""specVersion": "0.0.0","#),
+            ("reportFormat", r#""reportFormat": "CTRF","#),
+        ] {
+            let json = json.replace(line, "");
+            let results: Vec<_> = ReportReader::new(json.as_bytes()).unwrap().collect();
+
+            assert_eq!(results.len(), 2);
+            let error = results[1].as_ref().unwrap_err();
+            assert_eq!(error.path(), Some("$"));
+            assert!(error.to_string().contains(field));
+        }
+    }
+
+    #[test]
+    fn stream_malformed() {
+        let json = report_json(1);
+        let mut trailing = json.clone();
+        trailing.extend(b" }");
+
+        assert!(matches!(
+            Report::from_slice(&trailing),
+            Err(Error::Syntax { .. })
+        ));
+        let results: Vec<_> = ReportReader::new(trailing.as_slice()).unwrap().collect();
+        assert!(matches!(results[1], Err(Error::Syntax { .. })));
+
+        let json = String::from_utf8(json)
+            .unwrap()
+            .replacen(r#""tests": ["#, r#""tests": {"#, 1);
+        assert!(matches!(
+            ReportReader::new(json.as_bytes()),
+            Err(Error::Syntax { .. })
+        ));
+    }
+}
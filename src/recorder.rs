@@ -0,0 +1,176 @@
+use crate::{
+    results::{Results, ResultsBuilder},
+    test::Test,
+    tool::Tool,
+};
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    thread,
+    time::SystemTime,
+};
+
+const SHARD_COUNT: usize = 16;
+
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Threads are spread over the shards round-robin, so concurrent threads rarely contend
+    static SHARD: usize = NEXT_SHARD.fetch_add(1, Ordering::Relaxed) % SHARD_COUNT;
+}
+
+/// Collects Tests from many threads at once.
+/// Clones share the same recording, so a clone can be handed to each worker thread.
+/// Tests are stored in per-thread shards, and are returned in the order they were recorded.
+#[derive(Clone)]
+pub struct Recorder {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    tool: Tool,
+    sequence: AtomicU64,
+    shards: Vec<Mutex<Vec<(u64, Test)>>>,
+}
+
+impl Recorder {
+    pub fn new(tool: Tool) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                tool,
+                sequence: AtomicU64::new(0),
+                shards: (0..SHARD_COUNT).map(|_| Mutex::new(vec![])).collect(),
+            }),
+        }
+    }
+
+    /// Records a Test.  If it has no `thread_id`, the name (or else the ID) of the calling thread
+    /// is filled in.
+    pub fn record(&self, mut test: Test) {
+        if test.thread_id.is_none() {
+            let current = thread::current();
+            test.thread_id = Some(match current.name() {
+                Some(name) => String::from(name),
+                None => format!("{:?}", current.id()),
+            });
+        }
+
+        let sequence = self.inner.sequence.fetch_add(1, Ordering::Relaxed);
+        let shard = SHARD.with(|shard| *shard);
+        // A thread that panicked while recording leaves its shard intact, so keep using it
+        self.inner.shards[shard]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((sequence, test));
+    }
+
+    /// Returns the number of Tests recorded and not yet taken
+    pub fn len(&self) -> usize {
+        self.inner
+            .shards
+            .iter()
+            .map(|s| s.lock().unwrap_or_else(PoisonError::into_inner).len())
+            .sum()
+    }
+
+    /// Returns true if no Tests are waiting to be taken
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Takes all recorded Tests into a new ResultsBuilder, e.g. to set the environment before
+    /// building.  The recorder is left empty.
+    pub fn builder(&self) -> ResultsBuilder {
+        let mut tests: Vec<(u64, Test)> = self
+            .inner
+            .shards
+            .iter()
+            .flat_map(|s| std::mem::take(&mut *s.lock().unwrap_or_else(PoisonError::into_inner)))
+            .collect();
+        tests.sort_by_key(|(sequence, _)| *sequence);
+
+        let mut builder = ResultsBuilder::new(self.inner.tool.clone());
+        for (_, test) in tests {
+            builder.add_test(test);
+        }
+
+        builder
+    }
+
+    /// Takes all recorded Tests and builds the final Results.  The recorder is left empty.
+    pub fn build(&self, start: SystemTime, stop: SystemTime) -> Results {
+        self.builder().build(start, stop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test::Status;
+
+    use std::time::Duration;
+
+    #[test]
+    fn record_from_many_threads() {
+        const THREADS: usize = 8;
+        const TESTS: usize = 100;
+
+        let recorder = Recorder::new(Tool::new("ctrf-rs", None));
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let recorder = recorder.clone();
+                thread::Builder::new()
+                    .name(format!("worker{t}"))
+                    .spawn(move || {
+                        for i in 0..TESTS {
+                            recorder.record(Test::new(
+                                format!("{t}-{i}"),
+                                Status::Passed,
+                                Duration::from_millis(1),
+                            ));
+                        }
+                    })
+                    .unwrap()
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(recorder.len(), THREADS * TESTS);
+
+        let time = SystemTime::now();
+        let results = recorder.build(time, time);
+
+        assert!(recorder.is_empty());
+        assert!(results.validate().is_ok());
+        assert_eq!(results.tests().len(), THREADS * TESTS);
+        for t in 0..THREADS {
+            let thread_id = format!("worker{t}");
+            let names: Vec<_> = results
+                .tests()
+                .iter()
+                .filter(|test| test.thread_id.as_deref() == Some(thread_id.as_str()))
+                .map(|test| test.name().to_string())
+                .collect();
+            let expected: Vec<_> = (0..TESTS).map(|i| format!("{t}-{i}")).collect();
+            assert_eq!(names, expected);
+        }
+    }
+
+    #[test]
+    fn keep_explicit_thread_id() {
+        let recorder = Recorder::new(Tool::new("ctrf-rs", None));
+        let mut test = Test::new(String::from("t"), Status::Passed, Duration::from_millis(1));
+        test.thread_id = Some(String::from("custom"));
+
+        recorder.record(test);
+        let time = SystemTime::now();
+        let results = recorder.build(time, time);
+
+        assert_eq!(results.tests()[0].thread_id.as_deref(), Some("custom"));
+    }
+}
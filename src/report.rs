@@ -1,15 +1,30 @@
-use crate::{extra::Extra, impl_extra, results::Results};
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+use crate::compression::Compression;
+use crate::{
+    compression,
+    error::{Error, Result},
+    extra::Extra,
+    impl_extra,
+    results::Results,
+    test::attachment::{check_screenshot, AttachmentIssue, AttachmentProblem},
+};
 
 use std::{
     collections::HashMap,
-    io::{Read, Write},
+    fmt, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
     str::FromStr,
     time::SystemTime,
 };
 
+use base64::{engine::general_purpose::STANDARD, Engine};
 use semver::Version;
-use serde::{de::Error, Deserialize, Deserializer, Serialize};
-use serde_json::{Result, Value};
+use serde::{
+    de::{self, DeserializeSeed, Error as _, MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use serde_json::Value;
 use uuid::Uuid;
 
 pub const REPORT_FORMAT: &str = "CTRF";
@@ -17,7 +32,7 @@ pub const SPEC_VERSION: Version = Version::new(0, 0, 0);
 
 /// Top-level element for a CTRF report.
 /// Corresponds to the spec's ["Root"](https://ctrf.io/docs/specification/root) object.
-#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Report {
     #[serde(deserialize_with = "deserialize_format")]
@@ -53,19 +68,75 @@ impl Report {
         }
     }
 
-    /// Deserialize a `Report` instance from bytes of JSON text
+    /// Checks every attachment and screenshot in the report, returning those that are missing,
+    /// unreadable or whose declared content type is absent or contradicted by their contents.
+    /// Screenshots must be images.  Relative paths are resolved against `base_dir`.
+    pub fn check_attachments(&self, base_dir: impl AsRef<Path>) -> Vec<AttachmentProblem> {
+        let base_dir = base_dir.as_ref();
+        let mut problems = vec![];
+
+        for test in self.results.tests() {
+            for attachment in &test.attachments {
+                if let Some(issue) = attachment.check(base_dir) {
+                    problems.push(AttachmentProblem {
+                        test: String::from(test.name()),
+                        attachment: attachment.name.clone(),
+                        path: attachment.path.clone(),
+                        issue,
+                    });
+                }
+            }
+            if let Some(screenshot) = &test.screenshot {
+                if let Some(issue) = check_screenshot(screenshot, base_dir) {
+                    let embedded =
+                        issue != AttachmentIssue::Missing && !base_dir.join(screenshot).is_file();
+                    problems.push(AttachmentProblem {
+                        test: String::from(test.name()),
+                        attachment: String::from("screenshot"),
+                        path: if embedded {
+                            PathBuf::new()
+                        } else {
+                            PathBuf::from(screenshot)
+                        },
+                        issue,
+                    });
+                }
+            }
+        }
+
+        problems
+    }
+
+    /// Deserialize a `Report` instance from bytes of JSON text.
+    /// gzip or zstd compressed input is detected and decompressed, given the matching feature.
     pub fn from_slice(s: &[u8]) -> Result<Self> {
-        serde_json::from_slice(s)
+        let s = compression::decompress(s)?;
+        let mut deserializer = serde_json::Deserializer::from_slice(&s);
+        let report = checked(&mut deserializer)?;
+        deserializer.end()?;
+
+        Ok(report)
     }
 
     /// Interpret a `serde_json::Value` as a `Report` instance
     pub fn from_value(v: Value) -> Result<Self> {
-        serde_json::from_value(v)
+        checked(v)
     }
 
-    /// Deserialize a `Report` instance from an I/O stream of JSON text
+    /// Deserialize a `Report` instance from an I/O stream of JSON text, which may be compressed
+    /// as for [`Report::from_slice`].  The stream is parsed as it is read.
     pub fn from_reader(r: impl Read) -> Result<Self> {
-        serde_json::from_reader(r)
+        let r = io::BufReader::new(compression::decompress_reader(r)?);
+        let mut deserializer = serde_json::Deserializer::from_reader(r);
+        let report = checked(&mut deserializer)?;
+        deserializer.end()?;
+
+        Ok(report)
+    }
+
+    /// Checks that the summary agrees with the contained tests
+    pub fn validate(&self) -> Result<()> {
+        self.results.validate()
     }
 
     /// Borrows the contained Results
@@ -73,43 +144,239 @@ impl Report {
         &self.results
     }
 
+    #[cfg(any(feature = "bundle", feature = "redact"))]
+    pub(crate) fn results_mut(&mut self) -> &mut Results {
+        &mut self.results
+    }
+
+    /// Embeds every attachment as a base64 data URI and every screenshot file as base64 text,
+    /// so the report remains usable once copied elsewhere.
+    /// Relative paths are resolved against `base_dir`.
+    pub fn inline_attachments(&mut self, base_dir: impl AsRef<Path>) -> io::Result<()> {
+        let base_dir = base_dir.as_ref();
+
+        for test in self.results.tests_mut() {
+            for attachment in &mut test.attachments {
+                attachment.inline(base_dir)?;
+            }
+
+            if let Some(screenshot) = &mut test.screenshot {
+                let file = base_dir.join(&screenshot);
+                if file.is_file() {
+                    *screenshot = STANDARD.encode(fs::read(file)?);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Outputs the report as a String of JSON
     pub fn to_string(&self) -> Result<String> {
-        serde_json::to_string(self)
+        Ok(serde_json::to_string(self)?)
     }
 
     /// Outputs the report as a pretty-printed String of JSON
     pub fn to_string_pretty(&self) -> Result<String> {
-        serde_json::to_string_pretty(self)
+        Ok(serde_json::to_string_pretty(self)?)
     }
 
     /// Outputs the report as a JSON byte vector
     pub fn to_vec(&self) -> Result<Vec<u8>> {
-        serde_json::to_vec(self)
+        Ok(serde_json::to_vec(self)?)
     }
 
     /// Outputs the report as a pretty-printed JSON byte vector
     pub fn to_vec_pretty(&self) -> Result<Vec<u8>> {
-        serde_json::to_vec_pretty(self)
+        Ok(serde_json::to_vec_pretty(self)?)
     }
 
     /// Outputs the report as JSON to the provided I/O stream
     pub fn to_writer(&self, writer: impl Write) -> Result<()> {
-        serde_json::to_writer(writer, self)
+        Ok(serde_json::to_writer(writer, self)?)
     }
 
     /// Outputs the report as pretty-printed JSON to the provided I/O stream
     pub fn to_writer_pretty(&self, writer: impl Write) -> Result<()> {
-        serde_json::to_writer_pretty(writer, self)
+        Ok(serde_json::to_writer_pretty(writer, self)?)
+    }
+
+    /// Outputs the report as a compressed JSON byte vector
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    pub fn to_vec_compressed(&self, compression: Compression) -> Result<Vec<u8>> {
+        let mut buf = vec![];
+        self.to_writer_compressed(&mut buf, compression)?;
+
+        Ok(buf)
+    }
+
+    /// Outputs the report as a compressed, pretty-printed JSON byte vector
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    pub fn to_vec_pretty_compressed(&self, compression: Compression) -> Result<Vec<u8>> {
+        let mut buf = vec![];
+        self.to_writer_pretty_compressed(&mut buf, compression)?;
+
+        Ok(buf)
+    }
+
+    /// Outputs the report as compressed JSON to the provided I/O stream
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    pub fn to_writer_compressed(&self, writer: impl Write, compression: Compression) -> Result<()> {
+        compression.compress(writer, |w| Ok(serde_json::to_writer(w, self)?))
+    }
+
+    /// Outputs the report as compressed, pretty-printed JSON to the provided I/O stream
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    pub fn to_writer_pretty_compressed(
+        &self,
+        writer: impl Write,
+        compression: Compression,
+    ) -> Result<()> {
+        compression.compress(writer, |w| Ok(serde_json::to_writer_pretty(w, self)?))
     }
 }
 
 impl FromStr for Report {
-    type Err = serde_json::Error;
+    type Err = Error;
 
     /// Deserialize a `Report` instance from a string of JSON text
     fn from_str(s: &str) -> Result<Self> {
-        serde_json::from_str(s)
+        Self::from_slice(s.as_bytes())
+    }
+}
+
+/// Returns true if documents of the given spec version can be read by this crate
+pub fn is_supported_spec_version(version: &Version) -> bool {
+    version.major == SPEC_VERSION.major
+        && (version.major, version.minor) <= (SPEC_VERSION.major, SPEC_VERSION.minor)
+}
+
+fn check_format(format: String) -> Result<String> {
+    if format == REPORT_FORMAT {
+        Ok(format)
+    } else {
+        Err(Error::Format { found: format })
+    }
+}
+
+fn check_spec_version(version: Version) -> Result<Version> {
+    if is_supported_spec_version(&version) {
+        Ok(version)
+    } else {
+        Err(Error::UnsupportedSpecVersion { found: version })
+    }
+}
+
+/// Deserializes a Report in a single pass, failing with [`Error::Format`] or
+/// [`Error::UnsupportedSpecVersion`] as soon as the header is read
+fn checked<'de, D>(deserializer: D) -> Result<Report>
+where
+    D: Deserializer<'de, Error = serde_json::Error>,
+{
+    let mut header = None;
+    let mut track = serde_path_to_error::Track::new();
+    let result = CheckedReport {
+        header: &mut header,
+    }
+    .deserialize(serde_path_to_error::Deserializer::new(
+        deserializer,
+        &mut track,
+    ));
+
+    match (result, header) {
+        (Ok(report), _) => Ok(report),
+        (Err(_), Some(header)) => Err(header),
+        (Err(e), None) => Err(Error::tracked(
+            "$",
+            serde_path_to_error::Error::new(track.path(), e),
+        )),
+    }
+}
+
+/// Visits the root object, keeping a header error in `header` so it can be returned in place of
+/// the deserializer's error
+struct CheckedReport<'a> {
+    header: &'a mut Option<Error>,
+}
+
+/// Keeps a header error and hands the deserializer an error of its own type
+fn reject<E: de::Error>(header: &mut Option<Error>, e: Error) -> E {
+    let error = E::custom(&e);
+    *header = Some(e);
+
+    error
+}
+
+impl<'de> DeserializeSeed<'de> for CheckedReport<'_> {
+    type Value = Report;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Report, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for CheckedReport<'_> {
+    type Value = Report;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a CTRF report")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Report, A::Error> {
+        let mut report_format = None;
+        let mut spec_version = None;
+        let mut report_id = None;
+        let mut timestamp = None;
+        let mut generated_by = None;
+        let mut results = None;
+        let mut extra = None;
+
+        fn set<T, E: de::Error>(
+            slot: &mut Option<T>,
+            value: T,
+            key: &'static str,
+        ) -> std::result::Result<(), E> {
+            match slot.replace(value) {
+                Some(_) => Err(E::duplicate_field(key)),
+                None => Ok(()),
+            }
+        }
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "reportFormat" => {
+                    let format =
+                        check_format(map.next_value()?).map_err(|e| reject(self.header, e))?;
+                    set(&mut report_format, format, "reportFormat")?;
+                }
+                "specVersion" => {
+                    let version = check_spec_version(map.next_value()?)
+                        .map_err(|e| reject(self.header, e))?;
+                    set(&mut spec_version, version, "specVersion")?;
+                }
+                "reportId" => set(&mut report_id, map.next_value()?, "reportId")?,
+                "timestamp" => set(&mut timestamp, map.next_value()?, "timestamp")?,
+                "generatedBy" => set(&mut generated_by, map.next_value()?, "generatedBy")?,
+                "results" => set(&mut results, map.next_value()?, "results")?,
+                "extra" => set(&mut extra, map.next_value()?, "extra")?,
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(Report {
+            report_format: report_format.ok_or_else(|| A::Error::missing_field("reportFormat"))?,
+            spec_version: spec_version.ok_or_else(|| A::Error::missing_field("specVersion"))?,
+            report_id: report_id.flatten(),
+            timestamp: timestamp.flatten(),
+            generated_by: generated_by.flatten(),
+            results: results.ok_or_else(|| A::Error::missing_field("results"))?,
+            extra: extra.unwrap_or_default(),
+        })
     }
 }
 
@@ -134,9 +401,19 @@ impl_extra!(Report);
 mod tests {
     use super::*;
 
-    use crate::{results::ResultsBuilder, tool::Tool};
+    use crate::{
+        results::ResultsBuilder,
+        test::{
+            attachment::{Attachment, AttachmentIssue},
+            Status, Test,
+        },
+        tool::Tool,
+    };
 
-    use std::time::{Duration, UNIX_EPOCH};
+    use std::{
+        path::PathBuf,
+        time::{Duration, UNIX_EPOCH},
+    };
 
     const TEMPLATE_JSON: &str = r#"{
   "reportFormat": "CTRF",
@@ -303,6 +580,7 @@ mod tests {
         match report_result {
             Ok(_) => panic!("report deserialization should have failed"),
             Err(e) => {
+                assert!(matches!(e, Error::Format { .. }));
                 if !e.to_string().contains(&exp_msg) {
                     panic!(
                         "deserialization result did not contain expected message \"{}\"",
@@ -313,5 +591,219 @@ mod tests {
         }
     }
 
+    #[test]
+    fn deserialize_unsupported_spec_version() {
+        let json = TEMPLATE_JSON
+            .replace("START", "0")
+            .replace("STOP", "0")
+            .replace("0.0.0", "1.0.0");
+
+        match Report::from_str(&json) {
+            Err(Error::UnsupportedSpecVersion { found }) => {
+                assert_eq!(found, Version::new(1, 0, 0))
+            }
+            other => panic!(
+                "expected an unsupported spec version error, got {:?}",
+                other
+            ),
+        }
+    }
+
+    #[test]
+    fn deserialize_schema_violation() {
+        let json = TEMPLATE_JSON
+            .replace("START", "0")
+            .replace("STOP", "0")
+            .replace(
+                r#""tests": []"#,
+                r#""tests": [{"name": "t", "status": "bogus", "duration": 0}]"#,
+            );
+
+        let err = Report::from_str(&json).expect_err("report deserialization should have failed");
+
+        assert!(matches!(err, Error::Schema { .. }));
+        assert_eq!(err.path(), Some("$.results.tests[0].status"));
+    }
+
+    #[test]
+    fn deserialize_from_value() {
+        let json = TEMPLATE_JSON.replace("START", "0").replace("STOP", "0");
+        let value: Value = serde_json::from_str(&json).unwrap();
+
+        assert!(Report::from_value(value.clone()).is_ok());
+
+        let mut bad = value;
+        bad["reportFormat"] = Value::from("JUNIT");
+        assert!(matches!(
+            Report::from_value(bad),
+            Err(Error::Format { found }) if found == "JUNIT"
+        ));
+    }
+
+    #[test]
+    fn deserialize_header_before_malformed_body() {
+        let json = r#"{"reportFormat": "JUNIT", "specVersion": "0.0.0", "results": {"#;
+
+        assert!(matches!(
+            Report::from_str(json),
+            Err(Error::Format { found }) if found == "JUNIT"
+        ));
+        assert!(matches!(
+            Report::from_str(&json.replace("JUNIT", "CTRF")),
+            Err(Error::Syntax { .. })
+        ));
+    }
+
+    #[test]
+    fn deserialize_from_stream() {
+        /// Yields a single byte per read
+        struct Trickle<'a>(&'a [u8]);
+
+        impl Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                match (self.0.split_first(), buf.first_mut()) {
+                    (Some((byte, rest)), Some(slot)) => {
+                        *slot = *byte;
+                        self.0 = rest;
+                        Ok(1)
+                    }
+                    _ => Ok(0),
+                }
+            }
+        }
+
+        let json = TEMPLATE_JSON.replace("START", "0").replace("STOP", "0");
+        let report = Report::from_reader(Trickle(json.as_bytes())).unwrap();
+        assert_eq!(report, Report::from_str(&json).unwrap());
+
+        let trailing = format!("{json} {{}}");
+        assert!(matches!(
+            Report::from_reader(Trickle(trailing.as_bytes())),
+            Err(Error::Syntax { .. })
+        ));
+    }
+
     // TODO: deserialize full JSON
+
+    #[test]
+    fn inline_attachments() {
+        let dir = std::env::temp_dir().join(format!("ctrf-rs-inline-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("out.txt"), "log contents").unwrap();
+        fs::write(dir.join("shot.png"), "png").unwrap();
+
+        let mut test = Test::new(String::from("t"), Status::Failed, Duration::from_millis(1));
+        test.attachments.push(Attachment {
+            name: String::from("log"),
+            content_type: String::from("text/plain"),
+            path: PathBuf::from("out.txt"),
+            extra: HashMap::new(),
+        });
+        test.screenshot = Some(String::from("shot.png"));
+        let mut builder = ResultsBuilder::new(Tool::new("ctrf-rs", None));
+        builder.add_test(test);
+        let time = SystemTime::now();
+        let mut report = Report::new(None, None, None, builder.build(time, time));
+
+        report.inline_attachments(&dir).unwrap();
+        let test = &report.results().tests()[0];
+
+        assert!(test.attachments[0].is_inline());
+        assert_eq!(
+            test.attachments[0].path,
+            PathBuf::from("data:text/plain;base64,bG9nIGNvbnRlbnRz")
+        );
+        assert_eq!(
+            test.attachments[0].data("/nonexistent").unwrap(),
+            b"log contents".to_vec()
+        );
+        assert_eq!(test.screenshot.as_deref(), Some("cG5n"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn check_attachments() {
+        let dir = std::env::temp_dir().join(format!("ctrf-rs-check-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("shot.png"), b"\xff\xd8\xff\xe0").unwrap();
+        fs::write(dir.join("out.log"), "log").unwrap();
+        fs::write(dir.join("report.docx"), b"PK\x03\x04").unwrap();
+        fs::write(dir.join("fake.png"), "not an image").unwrap();
+
+        let attachment = |name: &str, content_type: &str, path: &str| Attachment {
+            name: String::from(name),
+            content_type: String::from(content_type),
+            path: PathBuf::from(path),
+            extra: HashMap::new(),
+        };
+        let mut test = Test::new(String::from("t"), Status::Failed, Duration::from_millis(1));
+        test.attachments = vec![
+            attachment("log", "text/plain", "out.log"),
+            attachment("shot", "image/png", "shot.png"),
+            attachment("gone", "text/plain", "gone.txt"),
+            attachment("blank", "", "out.log"),
+            attachment("blank and gone", " ", "gone.txt"),
+            attachment("alias", "image/jpg", "shot.png"),
+            attachment(
+                "docx",
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+                "report.docx",
+            ),
+            attachment("fake", "image/png", "fake.png"),
+        ];
+        test.screenshot = Some(String::from("out.log"));
+        let mut embedded = test.clone();
+        embedded.screenshot = Some(STANDARD.encode(b"\x89PNG\r\n\x1a\n"));
+        embedded.attachments.clear();
+        let mut builder = ResultsBuilder::new(Tool::new("ctrf-rs", None));
+        builder.add_test(test);
+        builder.add_test(embedded);
+        let time = SystemTime::now();
+        let report = Report::new(None, None, None, builder.build(time, time));
+
+        let issues: Vec<_> = report
+            .check_attachments(&dir)
+            .into_iter()
+            .map(|p| (p.attachment, p.issue))
+            .collect();
+
+        assert_eq!(
+            issues,
+            vec![
+                (
+                    String::from("shot"),
+                    AttachmentIssue::ContentTypeMismatch {
+                        declared: String::from("image/png"),
+                        detected: String::from("image/jpeg"),
+                    }
+                ),
+                (String::from("gone"), AttachmentIssue::Missing),
+                (String::from("blank"), AttachmentIssue::MissingContentType),
+                (String::from("blank and gone"), AttachmentIssue::Missing),
+                (
+                    String::from("fake"),
+                    AttachmentIssue::ContentTypeMismatch {
+                        declared: String::from("image/png"),
+                        detected: String::from("text/plain"),
+                    }
+                ),
+                (
+                    String::from("screenshot"),
+                    AttachmentIssue::ContentTypeMismatch {
+                        declared: String::from("image/*"),
+                        detected: String::from("text/plain"),
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            Attachment::from_path("shot", dir.join("shot.png"))
+                .unwrap()
+                .content_type,
+            "image/jpeg"
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
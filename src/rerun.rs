@@ -0,0 +1,612 @@
+use crate::{
+    error::Result,
+    extra::Extra,
+    results::Results,
+    test::{Status, Test},
+};
+
+use std::{
+    fs,
+    path::{Component, Path},
+};
+
+/// Key of a test's `extra` map holding the command to re-run it, see
+/// [`Results::store_rerun_commands`]
+pub const RERUN_COMMAND_KEY: &str = "rerunCommand";
+
+/// The kind of Cargo target a test was compiled into
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Target {
+    Lib,
+    Bin(String),
+    Test(String),
+    Bench(String),
+    Example(String),
+    /// Not derivable from the test's `filepath`
+    Unknown,
+}
+
+/// Failed or flaky tests sharing a package and target, so one command re-runs them all
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RerunGroup {
+    /// None if the package is not derivable, e.g. for a single-package workspace
+    pub package: Option<String>,
+    pub target: Target,
+    pub tests: Vec<String>,
+}
+
+impl RerunGroup {
+    /// Returns the arguments following `cargo`, running exactly the tests of the group
+    pub fn cargo_test_args(&self) -> Vec<String> {
+        let mut args = vec![String::from("test")];
+        if let Some(package) = &self.package {
+            args.extend([String::from("-p"), package.clone()]);
+        }
+        match &self.target {
+            Target::Lib => args.push(String::from("--lib")),
+            Target::Bin(name) => args.extend([String::from("--bin"), name.clone()]),
+            Target::Test(name) => args.extend([String::from("--test"), name.clone()]),
+            Target::Bench(name) => args.extend([String::from("--bench"), name.clone()]),
+            Target::Example(name) => args.extend([String::from("--example"), name.clone()]),
+            Target::Unknown => {}
+        }
+        args.push(String::from("--"));
+        args.push(String::from("--exact"));
+        args.extend(self.tests.iter().cloned());
+
+        args
+    }
+
+    /// Returns a shell command line running exactly the tests of the group with `cargo test`
+    pub fn cargo_test_command(&self) -> String {
+        command("cargo", &self.cargo_test_args())
+    }
+
+    /// Returns the nextest filterset selecting the tests of the group
+    pub fn nextest_filterset(&self) -> String {
+        let tests = self
+            .tests
+            .iter()
+            .map(|test| format!("test(={test})"))
+            .collect::<Vec<_>>()
+            .join(" | ");
+
+        match self.binary_id() {
+            Some(binary_id) => format!("binary_id({binary_id}) & ({tests})"),
+            None => format!("({tests})"),
+        }
+    }
+
+    /// The nextest binary ID, known if the package and target are
+    fn binary_id(&self) -> Option<String> {
+        let package = self.package.as_ref()?;
+
+        match &self.target {
+            Target::Lib => Some(package.clone()),
+            Target::Bin(name) => Some(format!("{package}::bin/{name}")),
+            Target::Test(name) => Some(format!("{package}::{name}")),
+            Target::Bench(name) => Some(format!("{package}::bench/{name}")),
+            Target::Example(name) => Some(format!("{package}::example/{name}")),
+            Target::Unknown => None,
+        }
+    }
+}
+
+impl Results {
+    /// Groups the failed and flaky tests by package and target, derived from their `filepath`
+    /// following the Cargo layout (`<package>/src/lib.rs`, `<package>/tests/<name>.rs`, ...).
+    /// The package is named by the nearest `Cargo.toml` above the file, relative paths being
+    /// resolved against the current directory; if there is none, by the directory holding the
+    /// target directory.  The test's `suite` is read as a nextest binary ID (`<package>`,
+    /// `<package>::<test>`, `<package>::bin/<name>`, ...); it fills in what the `filepath` does
+    /// not tell and, naming a target, beats a package guessed from directory names.
+    /// Groups and the tests within them are in report order.
+    pub fn rerun_groups(&self) -> Vec<RerunGroup> {
+        let mut groups: Vec<RerunGroup> = vec![];
+
+        for test in self.tests().iter().filter(|t| needs_rerun(t)) {
+            let (package, target) = locate(test);
+            match groups
+                .iter_mut()
+                .find(|g| g.package == package && g.target == target)
+            {
+                Some(group) => group.tests.push(String::from(test.name())),
+                None => groups.push(RerunGroup {
+                    package,
+                    target,
+                    tests: vec![String::from(test.name())],
+                }),
+            }
+        }
+
+        groups
+    }
+
+    /// Returns one `cargo test` command line per group of failed and flaky tests
+    pub fn cargo_test_commands(&self) -> Vec<String> {
+        self.rerun_groups()
+            .iter()
+            .map(RerunGroup::cargo_test_command)
+            .collect()
+    }
+
+    /// Returns a single `cargo nextest run -E` command line selecting all failed and flaky tests,
+    /// or None if there are none
+    pub fn nextest_command(&self) -> Option<String> {
+        let groups = self.rerun_groups();
+        if groups.is_empty() {
+            return None;
+        }
+
+        let filterset = groups
+            .iter()
+            .map(|g| format!("({})", g.nextest_filterset()))
+            .collect::<Vec<_>>()
+            .join(" | ");
+
+        Some(command(
+            "cargo",
+            &[
+                String::from("nextest"),
+                String::from("run"),
+                String::from("-E"),
+                filterset,
+            ],
+        ))
+    }
+
+    /// Stores the `cargo test` command re-running each failed or flaky test in its `extra` under
+    /// [`RERUN_COMMAND_KEY`].  Returns the number of tests given a command.
+    pub fn store_rerun_commands(&mut self) -> Result<usize> {
+        let mut count = 0;
+
+        for test in self.tests_mut().iter_mut().filter(|t| needs_rerun(t)) {
+            let (package, target) = locate(test);
+            let group = RerunGroup {
+                package,
+                target,
+                tests: vec![String::from(test.name())],
+            };
+            test.set_extra(RERUN_COMMAND_KEY, &group.cargo_test_command())?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+}
+
+fn needs_rerun(test: &Test) -> bool {
+    test.status() == Status::Failed || test.flaky == Some(true)
+}
+
+/// Derives the package and target of a test from its filepath, then its suite
+fn locate(test: &Test) -> (Option<String>, Target) {
+    locate_in(test, Path::new(""))
+}
+
+/// As [`locate`], resolving relative filepaths against `base` to find their manifest
+fn locate_in(test: &Test, base: &Path) -> (Option<String>, Target) {
+    let (mut package, mut target, from_manifest) = match &test.filepath {
+        Some(filepath) => locate_file(&filepath.to_string_lossy().replace('\\', "/"), base),
+        None => (None, Target::Unknown, false),
+    };
+
+    let main = Target::Bin(String::new());
+    if let Some((suite_package, suite_target)) = test.suite.as_deref().and_then(parse_binary_id) {
+        let compatible = |t: &Target| *t == target || target == main && matches!(t, Target::Bin(_));
+        match suite_target {
+            // A binary ID naming its target beats a package guessed from directory names
+            Some(suite_target) if !from_manifest || target == Target::Unknown => {
+                package = Some(suite_package);
+                target = suite_target;
+            }
+            suite_target if package.is_none() && suite_target.as_ref().map_or(true, compatible) => {
+                package = Some(suite_package);
+                if let Some(suite_target) = suite_target {
+                    target = suite_target;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if target == main {
+        target = match &package {
+            Some(package) => Target::Bin(package.clone()),
+            None => Target::Unknown,
+        };
+    }
+
+    (package, target)
+}
+
+/// Derives the package and target from a `/` separated path, preferring the package's manifest.
+/// Also returns whether the package was named by a manifest rather than guessed.
+fn locate_file(filepath: &str, base: &Path) -> (Option<String>, Target, bool) {
+    let path = Path::new(filepath);
+    let guess = || {
+        let (package, target) = locate_components(&normal_components(path));
+        (package, target, false)
+    };
+
+    for dir in path.ancestors().skip(1) {
+        let manifest = base.join(dir).join("Cargo.toml");
+        if !manifest.is_file() {
+            continue;
+        }
+        let package = fs::read_to_string(manifest)
+            .ok()
+            .and_then(|manifest| package_name(&manifest));
+        let relative = path.strip_prefix(dir).map(normal_components);
+        return match (package, relative) {
+            (Some(package), Ok(components)) if !components.is_empty() => {
+                match target(&components[0], &components[1..]) {
+                    Some(target) => (Some(package), target, true),
+                    None => guess(),
+                }
+            }
+            _ => guess(),
+        };
+    }
+
+    guess()
+}
+
+/// Takes the last target directory, or the outermost of adjacent ones as in `src/tests/`, as
+/// the one below the package directory
+fn locate_components(components: &[String]) -> (Option<String>, Target) {
+    let is_target_dir = |c: &String| matches!(c.as_str(), "src" | "tests" | "benches" | "examples");
+    let mut position = match components.iter().rposition(is_target_dir) {
+        Some(position) => position,
+        None => return (None, Target::Unknown),
+    };
+    while position > 0 && is_target_dir(&components[position - 1]) {
+        position -= 1;
+    }
+
+    let package = position.checked_sub(1).map(|p| components[p].clone());
+    let target =
+        target(&components[position], &components[position + 1..]).unwrap_or(Target::Unknown);
+
+    (package, target)
+}
+
+/// The target of a file below a target directory of a package, or None if `dir` is not one.
+/// `src/main.rs` yields a Bin with an empty name, to be named after the package.
+fn target(dir: &str, rest: &[String]) -> Option<Target> {
+    let stem = |name: &str| String::from(name.strip_suffix(".rs").unwrap_or(name));
+
+    let target = match (dir, rest) {
+        ("src", [main]) if main == "main.rs" => Target::Bin(String::new()),
+        ("src", [bin, name, ..]) if bin == "bin" => Target::Bin(stem(name)),
+        ("src", _) => Target::Lib,
+        ("tests" | "benches" | "examples", []) => Target::Unknown,
+        ("tests", [name, ..]) => Target::Test(stem(name)),
+        ("benches", [name, ..]) => Target::Bench(stem(name)),
+        ("examples", [name, ..]) => Target::Example(stem(name)),
+        _ => return None,
+    };
+
+    Some(target)
+}
+
+fn normal_components(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Reads `name` from the `[package]` table of a Cargo manifest
+fn package_name(manifest: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if in_package {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim() == "name" {
+                    let value = value.trim();
+                    return Some(String::from(value.trim_matches(|c| c == '"' || c == '\'')));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Parses a nextest binary ID into its package and target; the target is None for a bare
+/// package, which may be its library or its main binary
+fn parse_binary_id(binary_id: &str) -> Option<(String, Option<Target>)> {
+    let is_name = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    };
+
+    let (package, target) = match binary_id.split_once("::") {
+        Some((package, name)) => {
+            let target = match name.split_once('/') {
+                Some(("bin", name)) => Target::Bin(String::from(name)),
+                Some(("bench", name)) => Target::Bench(String::from(name)),
+                Some(("example", name)) => Target::Example(String::from(name)),
+                Some(_) => return None,
+                None => Target::Test(String::from(name)),
+            };
+            (package, Some(target))
+        }
+        None => (binary_id, None),
+    };
+    let name_ok = match &target {
+        Some(
+            Target::Bin(name) | Target::Bench(name) | Target::Example(name) | Target::Test(name),
+        ) => is_name(name),
+        _ => true,
+    };
+
+    if is_name(package) && name_ok {
+        Some((String::from(package), target))
+    } else {
+        None
+    }
+}
+
+/// Joins a program and its arguments into a shell command line, quoting where needed
+fn command(program: &str, args: &[String]) -> String {
+    let mut line = String::from(program);
+    for arg in args {
+        line.push(' ');
+        if !arg.is_empty()
+            && arg
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c))
+        {
+            line.push_str(arg);
+        } else {
+            line.push_str(&format!("'{}'", arg.replace('\'', r"'\''")));
+        }
+    }
+
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{results::ResultsBuilder, tool::Tool};
+
+    use std::{
+        path::PathBuf,
+        time::{Duration, SystemTime},
+    };
+
+    use uuid::Uuid;
+
+    fn results() -> Results {
+        let mut builder = ResultsBuilder::new(Tool::new("ctrf-rs", None));
+        for (name, status, filepath) in [
+            (
+                "net::tests::connect",
+                Status::Failed,
+                "crates/net/src/lib.rs",
+            ),
+            ("net::tests::send", Status::Passed, "crates/net/src/lib.rs"),
+            (
+                "net::tests::timeout",
+                Status::Failed,
+                "crates/net/src/tcp.rs",
+            ),
+            ("round_trip", Status::Failed, "crates/net/tests/api.rs"),
+            ("cli_parses", Status::Passed, "src/main.rs"),
+        ] {
+            let mut test = Test::new(String::from(name), status, Duration::from_millis(1));
+            test.filepath = Some(PathBuf::from(filepath));
+            builder.add_test(test);
+        }
+        let mut flaky = Test::new(
+            String::from("retry"),
+            Status::Passed,
+            Duration::from_millis(1),
+        );
+        flaky.flaky = Some(true);
+        builder.add_test(flaky);
+
+        let time = SystemTime::now();
+        builder.build(time, time)
+    }
+
+    #[test]
+    fn cargo_test_commands() {
+        assert_eq!(
+            results().cargo_test_commands(),
+            [
+                "cargo test -p net --lib -- --exact net::tests::connect net::tests::timeout",
+                "cargo test -p net --test api -- --exact round_trip",
+                "cargo test -- --exact retry",
+            ]
+        );
+    }
+
+    #[test]
+    fn nextest_command() {
+        assert_eq!(
+            results().nextest_command().unwrap(),
+            "cargo nextest run -E '(binary_id(net) & (test(=net::tests::connect) | \
+             test(=net::tests::timeout))) | (binary_id(net::api) & (test(=round_trip))) | \
+             ((test(=retry)))'"
+        );
+    }
+
+    #[test]
+    fn store_commands() -> Result<()> {
+        let mut results = results();
+
+        assert_eq!(results.store_rerun_commands()?, 4);
+        assert_eq!(
+            results.tests()[3].get_extra::<String>(RERUN_COMMAND_KEY)?,
+            Some(String::from(
+                "cargo test -p net --test api -- --exact round_trip"
+            ))
+        );
+        assert_eq!(
+            results.tests()[1].get_extra::<String>(RERUN_COMMAND_KEY)?,
+            None
+        );
+
+        Ok(())
+    }
+
+    fn located(
+        filepath: Option<&str>,
+        suite: Option<&str>,
+        base: &Path,
+    ) -> (Option<String>, Target) {
+        let mut test = Test::new(String::from("t"), Status::Failed, Duration::ZERO);
+        test.filepath = filepath.map(PathBuf::from);
+        test.suite = suite.map(String::from);
+        locate_in(&test, base)
+    }
+
+    /// A directory that does not exist, so that no manifest is found below it
+    fn no_manifest() -> PathBuf {
+        std::env::temp_dir().join(format!("ctrf-rs-rerun-{}", Uuid::new_v4()))
+    }
+
+    #[test]
+    fn locate_targets() {
+        let base = no_manifest();
+        let target = |path: &str| located(Some(path), None, &base);
+
+        assert_eq!(
+            target("crates/net/src/lib.rs"),
+            (Some(String::from("net")), Target::Lib)
+        );
+        assert_eq!(
+            target("crates/net/tests/api/main.rs"),
+            (Some(String::from("net")), Target::Test(String::from("api")))
+        );
+        assert_eq!(
+            target("tools/src/bin/gen/main.rs"),
+            (
+                Some(String::from("tools")),
+                Target::Bin(String::from("gen"))
+            )
+        );
+        assert_eq!(
+            target("tools/src/main.rs"),
+            (
+                Some(String::from("tools")),
+                Target::Bin(String::from("tools"))
+            )
+        );
+        assert_eq!(
+            target("benches\\speed.rs"),
+            (None, Target::Bench(String::from("speed")))
+        );
+        assert_eq!(target("src/main.rs"), (None, Target::Unknown));
+        assert_eq!(
+            target("/home/ci/src/proj/src/lib.rs"),
+            (Some(String::from("proj")), Target::Lib)
+        );
+        assert_eq!(
+            target("net/src/tests/mod.rs"),
+            (Some(String::from("net")), Target::Lib)
+        );
+        assert_eq!(target("README.md"), (None, Target::Unknown));
+    }
+
+    #[test]
+    fn locate_by_manifest() {
+        let dir = std::env::temp_dir().join(format!("ctrf-rs-rerun-{}", Uuid::new_v4()));
+        for (path, name) in [("", "app"), ("crates/net-dir", "net")] {
+            fs::create_dir_all(dir.join(path)).unwrap();
+            fs::write(
+                dir.join(path).join("Cargo.toml"),
+                format!("[workspace]\n\n[package]\nname = \"{name}\"\nversion = \"0.1.0\"\n"),
+            )
+            .unwrap();
+        }
+        let target = |path: &str, suite: Option<&str>| located(Some(path), suite, &dir);
+
+        assert_eq!(
+            target("src/main.rs", None),
+            (Some(String::from("app")), Target::Bin(String::from("app")))
+        );
+        assert_eq!(
+            target("crates/net-dir/src/tests/mod.rs", None),
+            (Some(String::from("net")), Target::Lib)
+        );
+        assert_eq!(
+            target("crates/net-dir/benches/speed.rs", Some("tools::bin/gen")),
+            (
+                Some(String::from("net")),
+                Target::Bench(String::from("speed"))
+            )
+        );
+        assert_eq!(
+            located(
+                Some(&dir.join("crates/net-dir/src/lib.rs").to_string_lossy()),
+                None,
+                Path::new("")
+            ),
+            (Some(String::from("net")), Target::Lib)
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn locate_by_suite() {
+        let base = no_manifest();
+        let target = |filepath: Option<&str>, suite: &str| located(filepath, Some(suite), &base);
+
+        assert_eq!(
+            target(None, "tools::bin/gen"),
+            (
+                Some(String::from("tools")),
+                Target::Bin(String::from("gen"))
+            )
+        );
+        assert_eq!(
+            target(Some("benches/speed.rs"), "net::bench/speed"),
+            (
+                Some(String::from("net")),
+                Target::Bench(String::from("speed"))
+            )
+        );
+        assert_eq!(
+            target(Some("/home/ci/src/main.rs"), "tools::bin/gen"),
+            (
+                Some(String::from("tools")),
+                Target::Bin(String::from("gen"))
+            )
+        );
+        assert_eq!(
+            target(Some("main.rs"), "tools"),
+            (Some(String::from("tools")), Target::Unknown)
+        );
+        assert_eq!(target(None, "net::tests::connect"), (None, Target::Unknown));
+    }
+
+    #[test]
+    fn bench_binary_id() {
+        let group = RerunGroup {
+            package: Some(String::from("net")),
+            target: Target::Bench(String::from("speed")),
+            tests: vec![String::from("throughput")],
+        };
+
+        assert_eq!(
+            group.nextest_filterset(),
+            "binary_id(net::bench/speed) & (test(=throughput))"
+        );
+        assert_eq!(
+            group.cargo_test_command(),
+            "cargo test -p net --bench speed -- --exact throughput"
+        );
+    }
+}
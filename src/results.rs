@@ -1,5 +1,6 @@
 use crate::{
     environment::Environment,
+    error::{Error, Result},
     extra::Extra,
     impl_extra,
     summary::Summary,
@@ -15,7 +16,7 @@ use std::{
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Results {
     tool: Tool,
@@ -31,6 +32,91 @@ impl Results {
     pub fn all_passed(&self) -> bool {
         self.summary.all_passed()
     }
+
+    /// Borrows the contained Tests
+    pub fn tests(&self) -> &[Test] {
+        &self.tests
+    }
+
+    /// Borrows the Summary
+    pub fn summary(&self) -> &Summary {
+        &self.summary
+    }
+
+    /// Borrows the Tool
+    pub fn tool(&self) -> &Tool {
+        &self.tool
+    }
+
+    /// Borrows the Environment, if present
+    pub fn environment(&self) -> Option<&Environment> {
+        self.environment.as_ref()
+    }
+
+    /// Checks that the Summary agrees with the contained Tests
+    pub fn validate(&self) -> Result<()> {
+        let inconsistency = |field: &str, message: String| Error::Consistency {
+            path: format!("$.results.summary.{field}"),
+            message,
+        };
+
+        if self.summary.tests() != self.tests.len() {
+            return Err(inconsistency(
+                "tests",
+                format!(
+                    "summary counts {} tests but {} are present",
+                    self.summary.tests(),
+                    self.tests.len()
+                ),
+            ));
+        }
+
+        for status in Status::ALL {
+            let expected = self.summary.count(status);
+            let actual = self.tests.iter().filter(|t| t.status() == status).count();
+            if expected != actual {
+                return Err(inconsistency(
+                    status.as_str(),
+                    format!(
+                        "summary counts {expected} {} tests but {actual} are present",
+                        status.as_str()
+                    ),
+                ));
+            }
+        }
+
+        if self.summary.start() > self.summary.stop() {
+            return Err(inconsistency(
+                "stop",
+                String::from("run stops before it starts"),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn tests_mut(&mut self) -> &mut [Test] {
+        &mut self.tests
+    }
+
+    #[cfg(feature = "redact")]
+    pub(crate) fn tool_mut(&mut self) -> &mut Tool {
+        &mut self.tool
+    }
+
+    #[cfg(feature = "redact")]
+    pub(crate) fn environment_mut(&mut self) -> Option<&mut Environment> {
+        self.environment.as_mut()
+    }
+
+    pub(crate) fn summary_mut(&mut self) -> &mut Summary {
+        &mut self.summary
+    }
+
+    /// Sets the summary's per-status counts from the contained Tests
+    pub(crate) fn recount(&mut self) {
+        self.summary.count_tests(&self.tests);
+    }
 }
 
 pub struct ResultsBuilder {
@@ -56,46 +142,27 @@ impl ResultsBuilder {
     }
 
     /// Sets the Environment, can be None
-    pub fn environment(mut self, environment: Option<Environment>) {
+    pub fn environment(&mut self, environment: Option<Environment>) {
         self.environment = environment;
     }
 
-    /// Builds and returns the final Results instance
+    /// Builds and returns the final Results instance.
+    /// Tests without an ID are given their [`Test::stable_id`].
     pub fn build(self, start: SystemTime, stop: SystemTime) -> Results {
         let ResultsBuilder {
             tool,
-            tests,
+            mut tests,
             environment,
             extra,
         } = self;
 
+        for test in &mut tests {
+            test.assign_id();
+        }
+
         let mut summary = Summary::new(start, stop);
 
-        summary.passed(
-            tests
-                .iter()
-                .filter(|t| t.status() == Status::Passed)
-                .count(),
-        );
-        summary.failed(
-            tests
-                .iter()
-                .filter(|t| t.status() == Status::Failed)
-                .count(),
-        );
-        summary.pending(
-            tests
-                .iter()
-                .filter(|t| t.status() == Status::Pending)
-                .count(),
-        );
-        summary.skipped(
-            tests
-                .iter()
-                .filter(|t| t.status() == Status::Skipped)
-                .count(),
-        );
-        summary.other(tests.iter().filter(|t| t.status() == Status::Other).count());
+        summary.count_tests(&tests);
 
         let mut suites = HashSet::new();
         for t in &tests {
@@ -131,6 +198,27 @@ mod tests {
 
     use serde_json::Result;
 
+    #[test]
+    fn validate_consistency() {
+        let mut builder = ResultsBuilder::new(Tool::new("ctrf-rs", None));
+        builder.add_test(Test::new(
+            String::from("fail"),
+            Status::Failed,
+            Duration::from_millis(0),
+        ));
+        let time = SystemTime::now();
+        let mut results = builder.build(time, time);
+
+        assert!(results.validate().is_ok());
+
+        results.summary.failed(0);
+        results.summary.passed(1);
+        match results.validate() {
+            Err(Error::Consistency { path, .. }) => assert_eq!(path, "$.results.summary.passed"),
+            other => panic!("expected a consistency error, got {:?}", other),
+        }
+    }
+
     #[test]
     fn add_passed() -> Result<()> {
         const TEST_COUNT: usize = 2;
@@ -0,0 +1,273 @@
+use crate::{
+    error::Result,
+    extra::{Extra, ExtraExtension},
+    results::Results,
+    test::Test,
+};
+
+use std::{cmp::Reverse, collections::BTreeMap};
+
+use serde::{Deserialize, Serialize};
+
+/// Statistics over a set of test durations, in milliseconds.
+/// Percentiles use the nearest-rank method; the standard deviation is that of the population.
+/// All values are zero for an empty set.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DurationStats {
+    pub count: usize,
+    pub total: u64,
+    pub mean: f64,
+    pub median: f64,
+    pub p90: u64,
+    pub p95: u64,
+    pub p99: u64,
+    pub std_dev: f64,
+    pub min: u64,
+    pub max: u64,
+}
+
+impl DurationStats {
+    /// Computes the statistics over the given durations in milliseconds
+    pub fn from_millis(durations: impl IntoIterator<Item = u64>) -> Self {
+        let mut sorted: Vec<u64> = durations.into_iter().collect();
+        if sorted.is_empty() {
+            return Self::default();
+        }
+        sorted.sort_unstable();
+
+        let count = sorted.len();
+        let total: u64 = sorted.iter().sum();
+        let mean = total as f64 / count as f64;
+        let median = if count % 2 == 0 {
+            (sorted[count / 2 - 1] + sorted[count / 2]) as f64 / 2.0
+        } else {
+            sorted[count / 2] as f64
+        };
+        let variance = sorted
+            .iter()
+            .map(|&d| (d as f64 - mean).powi(2))
+            .sum::<f64>()
+            / count as f64;
+        let percentile = |p: usize| sorted[((p * count).div_ceil(100)).max(1) - 1];
+
+        Self {
+            count,
+            total,
+            mean,
+            median,
+            p90: percentile(90),
+            p95: percentile(95),
+            p99: percentile(99),
+            std_dev: variance.sqrt(),
+            min: sorted[0],
+            max: sorted[count - 1],
+        }
+    }
+
+    /// Computes the statistics over the durations of the given Tests
+    pub fn from_tests<'a>(tests: impl IntoIterator<Item = &'a Test>) -> Self {
+        Self::from_millis(tests.into_iter().map(|t| t.duration().as_millis() as u64))
+    }
+}
+
+/// One of the slowest tests of a run
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowTest {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suite: Option<String>,
+    pub duration: u64,
+}
+
+/// One of the slowest suites of a run, by total duration
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowSuite {
+    pub suite: String,
+    pub tests: usize,
+    pub duration: u64,
+}
+
+/// Duration analysis of a run, stored in `summary.extra` by [`Results::record_duration_stats`]
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DurationReport {
+    pub overall: DurationStats,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub suites: BTreeMap<String, DurationStats>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, DurationStats>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slowest_tests: Vec<SlowTest>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slowest_suites: Vec<SlowSuite>,
+}
+
+impl ExtraExtension for DurationReport {
+    const KEY: &'static str = "durationStats";
+}
+
+impl Results {
+    /// Computes duration statistics over all Tests
+    pub fn duration_stats(&self) -> DurationStats {
+        DurationStats::from_tests(self.tests())
+    }
+
+    /// Computes duration statistics per suite.  Tests without a suite are left out.
+    pub fn suite_duration_stats(&self) -> BTreeMap<String, DurationStats> {
+        self.grouped_stats(|test| test.suite().iter().cloned().collect())
+    }
+
+    /// Computes duration statistics per tag.  A test with several tags counts towards each.
+    pub fn tag_duration_stats(&self) -> BTreeMap<String, DurationStats> {
+        self.grouped_stats(|test| test.tags.clone())
+    }
+
+    /// Returns up to `n` Tests, slowest first.  Equally slow tests keep their report order.
+    pub fn slowest_tests(&self, n: usize) -> Vec<&Test> {
+        let mut tests: Vec<&Test> = self.tests().iter().collect();
+        tests.sort_by_key(|test| Reverse(test.duration()));
+        tests.truncate(n);
+
+        tests
+    }
+
+    /// Returns up to `n` suites with the largest total duration, slowest first
+    pub fn slowest_suites(&self, n: usize) -> Vec<SlowSuite> {
+        let mut suites: Vec<SlowSuite> = self
+            .suite_duration_stats()
+            .into_iter()
+            .map(|(suite, stats)| SlowSuite {
+                suite,
+                tests: stats.count,
+                duration: stats.total,
+            })
+            .collect();
+        suites.sort_by_key(|suite| Reverse(suite.duration));
+        suites.truncate(n);
+
+        suites
+    }
+
+    /// Computes the full duration analysis, listing the `top` slowest tests and suites
+    pub fn duration_report(&self, top: usize) -> DurationReport {
+        DurationReport {
+            overall: self.duration_stats(),
+            suites: self.suite_duration_stats(),
+            tags: self.tag_duration_stats(),
+            slowest_tests: self
+                .slowest_tests(top)
+                .into_iter()
+                .map(|test| SlowTest {
+                    name: String::from(test.name()),
+                    suite: test.suite().clone(),
+                    duration: test.duration().as_millis() as u64,
+                })
+                .collect(),
+            slowest_suites: self.slowest_suites(top),
+        }
+    }
+
+    /// Stores the [`DurationReport`] in the Summary's `extra` under `durationStats`
+    pub fn record_duration_stats(&mut self, top: usize) -> Result<()> {
+        let report = self.duration_report(top);
+        self.summary_mut().set_extension(&report)?;
+
+        Ok(())
+    }
+
+    fn grouped_stats(
+        &self,
+        keys: impl Fn(&Test) -> Vec<String>,
+    ) -> BTreeMap<String, DurationStats> {
+        let mut groups: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+        for test in self.tests() {
+            for key in keys(test) {
+                groups
+                    .entry(key)
+                    .or_default()
+                    .push(test.duration().as_millis() as u64);
+            }
+        }
+
+        groups
+            .into_iter()
+            .map(|(key, durations)| (key, DurationStats::from_millis(durations)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{results::ResultsBuilder, test::Status, tool::Tool};
+
+    use std::time::{Duration, SystemTime};
+
+    fn results() -> Results {
+        let mut builder = ResultsBuilder::new(Tool::new("ctrf-rs", None));
+        for (name, suite, tag, millis) in [
+            ("a", "fast", "unit", 1),
+            ("b", "fast", "unit", 3),
+            ("c", "slow", "io", 100),
+            ("d", "slow", "unit", 40),
+        ] {
+            let mut test = Test::new(
+                String::from(name),
+                Status::Passed,
+                Duration::from_millis(millis),
+            );
+            test.suite = Some(String::from(suite));
+            test.tags = vec![String::from(tag)];
+            builder.add_test(test);
+        }
+
+        let time = SystemTime::now();
+        builder.build(time, time)
+    }
+
+    #[test]
+    fn stats_of_durations() {
+        let stats = DurationStats::from_millis((1..=100).rev());
+
+        assert_eq!(stats.count, 100);
+        assert_eq!(stats.total, 5050);
+        assert_eq!(stats.mean, 50.5);
+        assert_eq!(stats.median, 50.5);
+        assert_eq!((stats.p90, stats.p95, stats.p99), (90, 95, 99));
+        assert_eq!((stats.min, stats.max), (1, 100));
+        assert!((stats.std_dev - 28.866).abs() < 0.001);
+
+        assert_eq!(DurationStats::from_millis([]), DurationStats::default());
+        assert_eq!(DurationStats::from_millis([7]).p99, 7);
+    }
+
+    #[test]
+    fn breakdowns_and_slowest() {
+        let results = results();
+
+        assert_eq!(results.duration_stats().total, 144);
+        assert_eq!(results.suite_duration_stats()["fast"].mean, 2.0);
+        assert_eq!(results.tag_duration_stats()["unit"].count, 3);
+
+        let slowest: Vec<_> = results.slowest_tests(2).iter().map(|t| t.name()).collect();
+        assert_eq!(slowest, ["c", "d"]);
+        assert_eq!(results.slowest_suites(1)[0].suite, "slow");
+        assert_eq!(results.slowest_suites(1)[0].duration, 140);
+    }
+
+    #[test]
+    fn record_in_summary() -> Result<()> {
+        let mut results = results();
+        results.record_duration_stats(3)?;
+
+        let recorded: DurationReport = results.summary().get_extension()?.unwrap();
+        assert_eq!(recorded, results.duration_report(3));
+        assert_eq!(recorded.slowest_tests.len(), 3);
+
+        Ok(())
+    }
+}
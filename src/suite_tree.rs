@@ -0,0 +1,261 @@
+use crate::{
+    results::Results,
+    test::{Status, Test},
+};
+
+use serde::Serialize;
+
+/// Separator of Rust module paths, e.g. in `crate::net::tcp::tests::connect`
+pub const MODULE_SEPARATOR: &str = "::";
+
+/// A node of the suite hierarchy, aggregating every test beneath it.
+/// Paths are made of the segments of a test's `suite` followed by those of its name, the last
+/// name segment being the test itself.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SuiteNode {
+    /// Last segment of the path, empty for the root
+    pub name: String,
+    /// Segments joined by the separator, empty for the root
+    pub path: String,
+    pub tests: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub pending: usize,
+    pub skipped: usize,
+    pub other: usize,
+    /// Sum of the test durations in milliseconds
+    pub duration: u64,
+    /// Aggregate status: failed if any test failed, otherwise other or pending if any are,
+    /// otherwise passed if any passed, otherwise skipped.  None for an empty tree.
+    pub status: Option<Status>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<SuiteNode>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub leaves: Vec<TestLeaf>,
+}
+
+/// A test directly below a SuiteNode
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TestLeaf {
+    /// Last segment of the test name
+    pub name: String,
+    /// Index of the test in `results.tests`
+    pub index: usize,
+    pub status: Status,
+    pub duration: u64,
+}
+
+impl SuiteNode {
+    /// Builds the hierarchy of the given Tests, splitting suites and names at `separator`.
+    /// An empty separator splits nothing, so each suite is a single node holding its tests.
+    pub fn build<'a>(tests: impl IntoIterator<Item = &'a Test>, separator: &str) -> Self {
+        let mut root = Self::default();
+        let split = |text: &'a str| -> Vec<&'a str> {
+            if separator.is_empty() {
+                vec![text]
+            } else {
+                text.split(separator).collect()
+            }
+        };
+
+        for (index, test) in tests.into_iter().enumerate() {
+            let mut segments: Vec<&str> = test
+                .suite()
+                .iter()
+                .flat_map(|suite| split(suite))
+                .chain(split(test.name()))
+                .filter(|segment| !segment.is_empty())
+                .collect();
+            let leaf = segments.pop().unwrap_or_default();
+
+            let duration = test.duration().as_millis() as u64;
+            let mut node = &mut root;
+            node.add(test.status(), duration);
+            for segment in segments {
+                node = node.child(segment, separator);
+                node.add(test.status(), duration);
+            }
+            node.leaves.push(TestLeaf {
+                name: String::from(leaf),
+                index,
+                status: test.status(),
+                duration,
+            });
+        }
+
+        root
+    }
+
+    /// Returns the count of tests beneath with the given status
+    pub fn count(&self, status: Status) -> usize {
+        match status {
+            Status::Passed => self.passed,
+            Status::Failed => self.failed,
+            Status::Pending => self.pending,
+            Status::Skipped => self.skipped,
+            Status::Other => self.other,
+        }
+    }
+
+    /// Finds the node at the given path below this one, joined by the separator used to build it
+    pub fn find(&self, path: &str) -> Option<&SuiteNode> {
+        if self.path == path {
+            return Some(self);
+        }
+
+        self.children.iter().find_map(|child| child.find(path))
+    }
+
+    /// Visits this node and all nodes beneath depth-first, parents before children, with their
+    /// depth below this node
+    pub fn walk(&self) -> Vec<(usize, &SuiteNode)> {
+        let mut nodes = vec![];
+        let mut stack = vec![(0, self)];
+
+        while let Some((depth, node)) = stack.pop() {
+            nodes.push((depth, node));
+            stack.extend(node.children.iter().rev().map(|child| (depth + 1, child)));
+        }
+
+        nodes
+    }
+
+    fn aggregate_status(&self) -> Option<Status> {
+        [
+            Status::Failed,
+            Status::Other,
+            Status::Pending,
+            Status::Passed,
+            Status::Skipped,
+        ]
+        .iter()
+        .copied()
+        .find(|status| self.count(*status) > 0)
+    }
+
+    fn add(&mut self, status: Status, duration: u64) {
+        self.tests += 1;
+        match status {
+            Status::Passed => self.passed += 1,
+            Status::Failed => self.failed += 1,
+            Status::Pending => self.pending += 1,
+            Status::Skipped => self.skipped += 1,
+            Status::Other => self.other += 1,
+        }
+        self.duration += duration;
+        self.status = self.aggregate_status();
+    }
+
+    fn child(&mut self, name: &str, separator: &str) -> &mut SuiteNode {
+        let position = match self.children.iter().position(|child| child.name == name) {
+            Some(position) => position,
+            None => {
+                let path = if self.path.is_empty() {
+                    String::from(name)
+                } else {
+                    format!("{}{separator}{name}", self.path)
+                };
+                self.children.push(SuiteNode {
+                    name: String::from(name),
+                    path,
+                    ..Self::default()
+                });
+                self.children.len() - 1
+            }
+        };
+
+        &mut self.children[position]
+    }
+}
+
+impl Results {
+    /// Builds the suite hierarchy of the contained Tests, splitting suites and names at
+    /// `separator`, e.g. [`MODULE_SEPARATOR`]
+    pub fn suite_tree(&self, separator: &str) -> SuiteNode {
+        SuiteNode::build(self.tests(), separator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{results::ResultsBuilder, tool::Tool};
+
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn build_module_tree() {
+        let mut builder = ResultsBuilder::new(Tool::new("ctrf-rs", None));
+        for (name, status, millis) in [
+            ("net::tcp::tests::connect", Status::Passed, 5),
+            ("net::tcp::tests::timeout", Status::Failed, 20),
+            ("net::udp::tests::send", Status::Skipped, 0),
+            ("parse", Status::Passed, 1),
+        ] {
+            let mut test = Test::new(String::from(name), status, Duration::from_millis(millis));
+            test.suite = Some(String::from("my_crate"));
+            builder.add_test(test);
+        }
+        let time = SystemTime::now();
+        let tree = builder.build(time, time).suite_tree(MODULE_SEPARATOR);
+
+        assert_eq!(tree.tests, 4);
+        assert_eq!(tree.children.len(), 1);
+
+        let net = tree.find("my_crate::net").unwrap();
+        assert_eq!((net.tests, net.duration), (3, 25));
+        assert_eq!(net.status, Some(Status::Failed));
+
+        let udp = tree.find("my_crate::net::udp::tests").unwrap();
+        assert_eq!(udp.status, Some(Status::Skipped));
+        assert_eq!(udp.leaves[0].name, "send");
+        assert_eq!(udp.leaves[0].index, 2);
+
+        let crate_node = tree.find("my_crate").unwrap();
+        assert_eq!(crate_node.leaves[0].name, "parse");
+        assert_eq!(crate_node.status, Some(Status::Failed));
+
+        let paths: Vec<_> = tree
+            .walk()
+            .into_iter()
+            .map(|(depth, node)| (depth, node.path.as_str()))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                (0, ""),
+                (1, "my_crate"),
+                (2, "my_crate::net"),
+                (3, "my_crate::net::tcp"),
+                (4, "my_crate::net::tcp::tests"),
+                (3, "my_crate::net::udp"),
+                (4, "my_crate::net::udp::tests"),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_separator() {
+        let mut test = Test::new(
+            String::from("net::connect"),
+            Status::Passed,
+            Duration::from_millis(1),
+        );
+        test.suite = Some(String::from("my_crate"));
+        let plain = Test::new(
+            String::from("parse"),
+            Status::Failed,
+            Duration::from_millis(1),
+        );
+
+        let tree = SuiteNode::build([&test, &plain], "");
+
+        assert_eq!(tree.children.len(), 1);
+        assert_eq!(tree.children[0].path, "my_crate");
+        assert_eq!(tree.children[0].leaves[0].name, "net::connect");
+        assert_eq!(tree.leaves[0].name, "parse");
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{extra::Extra, impl_extra};
+use crate::{
+    extra::Extra,
+    impl_extra,
+    test::{Status, Test},
+};
 
 use std::{
     collections::HashMap,
@@ -10,7 +14,7 @@ use serde_json::Value;
 
 /// Result summary element for a CTRF report.
 /// Corresponds to the spec's ["Summary"](https://ctrf.io/docs/specification/summary) object.
-#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Summary {
     tests: usize,
@@ -48,6 +52,37 @@ impl Summary {
         self.passed == self.tests
     }
 
+    /// Returns the total test count
+    pub fn tests(&self) -> usize {
+        self.tests
+    }
+
+    /// Returns the count of tests with the given status
+    pub fn count(&self, status: Status) -> usize {
+        match status {
+            Status::Passed => self.passed,
+            Status::Failed => self.failed,
+            Status::Pending => self.pending,
+            Status::Skipped => self.skipped,
+            Status::Other => self.other,
+        }
+    }
+
+    /// Returns the number of Suites, if recorded
+    pub fn suite_count(&self) -> Option<usize> {
+        self.suites
+    }
+
+    /// Returns the run's start time in milliseconds since the Unix epoch
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the run's stop time in milliseconds since the Unix epoch
+    pub fn stop(&self) -> u64 {
+        self.stop
+    }
+
     /// Sets the count of passed tests and updates the overall total
     pub fn passed(&mut self, count: usize) {
         self.passed = count;
@@ -83,11 +118,32 @@ impl Summary {
         self.update_tests();
     }
 
+    /// Sets the count of tests with the given status and updates the overall total
+    pub fn set_count(&mut self, status: Status, count: usize) {
+        match status {
+            Status::Passed => self.passed(count),
+            Status::Failed => self.failed(count),
+            Status::Pending => self.pending(count),
+            Status::Skipped => self.skipped(count),
+            Status::Other => self.other(count),
+        }
+    }
+
     /// Sets the number of Suites, can be None
     pub fn suites(&mut self, suites: Option<usize>) {
         self.suites = suites;
     }
 
+    /// Sets the per-status counts and the total from the given tests
+    pub(crate) fn count_tests(&mut self, tests: &[Test]) {
+        for status in Status::ALL {
+            self.set_count(
+                status,
+                tests.iter().filter(|t| t.status() == status).count(),
+            );
+        }
+    }
+
     /// Updates the total test count
     fn update_tests(&mut self) {
         self.tests = self.passed + self.failed + self.pending + self.skipped + self.other;
@@ -1,4 +1,5 @@
 use crate::{
+    canonical::sort_keys,
     extra::Extra,
     impl_extra,
     test::{attachment::Attachment, step::Step},
@@ -8,11 +9,12 @@ use std::{collections::HashMap, path::PathBuf, time::Duration, vec};
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use uuid::Uuid;
 
 pub mod attachment;
 pub mod step;
 
-#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum Status {
     Pending,
@@ -22,9 +24,33 @@ pub enum Status {
     Other,
 }
 
-#[derive(Deserialize, Serialize, Debug, PartialEq)]
+impl Status {
+    /// All statuses, in the order used by the summary
+    pub const ALL: [Status; 5] = [
+        Status::Passed,
+        Status::Failed,
+        Status::Pending,
+        Status::Skipped,
+        Status::Other,
+    ];
+
+    /// Returns the name used in CTRF documents
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Status::Pending => "pending",
+            Status::Skipped => "skipped",
+            Status::Failed => "failed",
+            Status::Passed => "passed",
+            Status::Other => "other",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Test {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
     name: String,
     status: Status,
     duration: u64,
@@ -44,7 +70,7 @@ pub struct Test {
     pub ai: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub raw_status: Option<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     pub test_type: Option<String>,
@@ -54,9 +80,9 @@ pub struct Test {
     pub retries: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub flaky: Option<bool>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub stdout: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub stderr: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thread_id: Option<String>,
@@ -68,9 +94,9 @@ pub struct Test {
     pub screenshot: Option<String>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub parameters: HashMap<String, Value>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub steps: Vec<Step>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub attachments: Vec<Attachment>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     extra: HashMap<String, Value>,
@@ -79,6 +105,7 @@ pub struct Test {
 impl Test {
     pub fn new(name: String, status: Status, duration: Duration) -> Self {
         Self {
+            id: None,
             name,
             status,
             duration: duration.as_millis() as u64,
@@ -108,10 +135,18 @@ impl Test {
         }
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn status(&self) -> Status {
         self.status
     }
 
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration)
+    }
+
     pub fn suite(&self) -> &Option<String> {
         &self.suite
     }
@@ -119,6 +154,92 @@ impl Test {
     pub fn fail_test(&mut self) {
         self.status = Status::Failed;
     }
+
+    pub(crate) fn set_status(&mut self, status: Status) {
+        self.status = status;
+    }
+
+    /// Derives a deterministic ID from the suite, name, filepath and parameters, so the same test
+    /// has the same ID in every run.  Parameter keys are sorted, and filepaths use `/` separators.
+    pub fn stable_id(&self) -> Uuid {
+        let filepath = self
+            .filepath
+            .as_ref()
+            .map(|path| path.to_string_lossy().replace('\\', "/"));
+        let parameters: Value = self.parameters.clone().into_iter().collect();
+        let identity = Value::from(vec![
+            Value::from(self.suite.clone()),
+            Value::from(self.name.clone()),
+            Value::from(filepath),
+            parameters,
+        ]);
+
+        Uuid::new_v5(
+            &TEST_ID_NAMESPACE,
+            sort_keys(identity).to_string().as_bytes(),
+        )
+    }
+
+    /// Sets the ID to the [`Test::stable_id`] unless one is present, returning the ID
+    pub fn assign_id(&mut self) -> Uuid {
+        match self.id {
+            Some(id) => id,
+            None => {
+                let id = self.stable_id();
+                self.id = Some(id);
+                id
+            }
+        }
+    }
 }
 
 impl_extra!(Test);
+
+/// Namespace of the UUIDv5 IDs derived by [`Test::stable_id`]
+pub const TEST_ID_NAMESPACE: Uuid = Uuid::from_u128(0x5f0c_9a3e_2b7d_4c61_8e94_d2a7_1b36_c0f5);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn test(parameters: &[(&str, Value)]) -> Test {
+        let mut test = Test::new(String::from("t"), Status::Passed, Duration::from_millis(1));
+        test.suite = Some(String::from("s"));
+        test.filepath = Some(PathBuf::from("tests/t.rs"));
+        for (key, value) in parameters {
+            test.parameters.insert(String::from(*key), value.clone());
+        }
+        test
+    }
+
+    #[test]
+    fn stable_id_is_deterministic() {
+        let a = test(&[("x", json!({"b": 1, "a": [2, 3]})), ("y", json!("z"))]);
+        let b = test(&[("y", json!("z")), ("x", json!({"a": [2, 3], "b": 1}))]);
+        let c = test(&[("x", json!(1))]);
+
+        assert_eq!(a.stable_id(), b.stable_id());
+        assert_ne!(a.stable_id(), c.stable_id());
+        assert_eq!(a.stable_id().get_version_num(), 5);
+    }
+
+    #[test]
+    fn assign_keeps_existing_id() {
+        let mut test = test(&[]);
+        let id = test.assign_id();
+        assert_eq!(test.id, Some(test.stable_id()));
+        assert_eq!(test.assign_id(), id);
+
+        let custom = Uuid::new_v4();
+        test.id = Some(custom);
+        assert_eq!(test.assign_id(), custom);
+
+        let json = serde_json::to_string(&test).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Test>(&json).unwrap().id,
+            Some(custom)
+        );
+    }
+}
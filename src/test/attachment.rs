@@ -1,11 +1,27 @@
 use crate::{extra::Extra, impl_extra};
 
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
 
+use base64::{engine::general_purpose::STANDARD, Engine};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub mod content_type;
+
+const DATA_URI_PREFIX: &str = "data:";
+const BASE64_PARAMETER: &str = ";base64";
+/// Content type a screenshot is checked against
+const SCREENSHOT_CONTENT_TYPE: &str = "image/*";
+/// Base64 characters encoding at least [`SNIFF_LEN`](content_type::SNIFF_LEN) bytes
+const SCREENSHOT_SNIFF_LEN: usize = (content_type::SNIFF_LEN + 2) / 3 * 4;
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Attachment {
     /// Required
@@ -18,4 +34,241 @@ pub struct Attachment {
     pub extra: HashMap<String, Value>,
 }
 
+impl Attachment {
+    /// Creates an Attachment for the file at `path`, detecting its content type from the
+    /// file's contents or, failing that, its extension
+    pub fn from_path(name: &str, path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let head = read_head(&path)?;
+
+        Ok(Self {
+            name: String::from(name),
+            content_type: String::from(content_type::detect(&head, &path)),
+            path,
+            extra: HashMap::new(),
+        })
+    }
+
+    /// Returns true if the contents are embedded in `path` as a data URI
+    pub fn is_inline(&self) -> bool {
+        self.path
+            .to_str()
+            .map_or(false, |p| p.starts_with(DATA_URI_PREFIX))
+    }
+
+    /// Reads the referenced file and embeds its contents in `path` as a base64 data URI.
+    /// Relative paths are resolved against `base_dir`.  Does nothing if already inline.
+    pub fn inline(&mut self, base_dir: impl AsRef<Path>) -> io::Result<()> {
+        if self.is_inline() {
+            return Ok(());
+        }
+
+        let data = fs::read(base_dir.as_ref().join(&self.path))?;
+        self.path = PathBuf::from(format!(
+            "{DATA_URI_PREFIX}{}{BASE64_PARAMETER},{}",
+            self.content_type,
+            STANDARD.encode(data)
+        ));
+
+        Ok(())
+    }
+
+    /// Returns the attachment contents, decoding inline data or reading the referenced file.
+    /// Relative paths are resolved against `base_dir`.
+    pub fn data(&self, base_dir: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+        match self.path.to_str().and_then(decode_data_uri) {
+            Some(decoded) => decoded,
+            None => fs::read(base_dir.as_ref().join(&self.path)),
+        }
+    }
+
+    /// Verifies that the attachment can be read and that its declared content type is plausible.
+    /// Relative paths are resolved against `base_dir`.
+    /// Only the first [`SNIFF_LEN`](content_type::SNIFF_LEN) bytes of a file are read.
+    pub fn check(&self, base_dir: impl AsRef<Path>) -> Option<AttachmentIssue> {
+        let head = match self.path.to_str().and_then(decode_data_uri) {
+            Some(decoded) => decoded,
+            None => read_head(&base_dir.as_ref().join(&self.path)),
+        };
+        let head = match head {
+            Ok(head) => head,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Some(AttachmentIssue::Missing),
+            Err(e) => return Some(AttachmentIssue::Unreadable(e.to_string())),
+        };
+
+        if self.content_type.trim().is_empty() {
+            return Some(AttachmentIssue::MissingContentType);
+        }
+
+        // Text formats carry no signature, so only binary types are checked
+        let detected = match content_type::sniff(&head) {
+            Some(detected) if content_type::compatible(&self.content_type, detected) => {
+                return None
+            }
+            Some(detected) => detected,
+            // A binary type without its signature; the extension is no evidence of the contents
+            None if content_type::has_signature(&self.content_type) => {
+                content_type::detect(&head, Path::new(""))
+            }
+            None => return None,
+        };
+
+        Some(AttachmentIssue::ContentTypeMismatch {
+            declared: self.content_type.clone(),
+            detected: String::from(detected),
+        })
+    }
+}
+
+/// Verifies that a test's `screenshot`, a file path, data URI or base64 text, holds an image.
+/// Relative paths are resolved against `base_dir`.
+pub fn check_screenshot(screenshot: &str, base_dir: impl AsRef<Path>) -> Option<AttachmentIssue> {
+    let path = base_dir.as_ref().join(screenshot);
+    // A prefix of a multiple of 4 characters decodes without padding
+    let prefix = screenshot.get(..SCREENSHOT_SNIFF_LEN).unwrap_or(screenshot);
+    let head = match decode_data_uri(screenshot) {
+        Some(decoded) => decoded,
+        None if path.is_file() => read_head(&path),
+        None => match STANDARD.decode(prefix) {
+            Ok(decoded) => Ok(decoded),
+            // Neither a file nor base64 text, so presumably a missing file
+            Err(_) => return Some(AttachmentIssue::Missing),
+        },
+    };
+    let head = match head {
+        Ok(head) => head,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Some(AttachmentIssue::Missing),
+        Err(e) => return Some(AttachmentIssue::Unreadable(e.to_string())),
+    };
+
+    match content_type::sniff(&head) {
+        Some(detected) if detected.starts_with("image/") => None,
+        detected => Some(AttachmentIssue::ContentTypeMismatch {
+            declared: String::from(SCREENSHOT_CONTENT_TYPE),
+            detected: String::from(
+                detected.unwrap_or_else(|| content_type::detect(&head, Path::new(""))),
+            ),
+        }),
+    }
+}
+
+/// A problem found when checking an Attachment
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttachmentIssue {
+    /// The content type is blank
+    MissingContentType,
+    /// The referenced file does not exist
+    Missing,
+    /// The referenced file or inline data could not be read
+    Unreadable(String),
+    /// The contents do not match the declared content type
+    ContentTypeMismatch { declared: String, detected: String },
+}
+
+impl Display for AttachmentIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingContentType => write!(f, "content type is missing"),
+            Self::Missing => write!(f, "file does not exist"),
+            Self::Unreadable(e) => write!(f, "file is not readable: {e}"),
+            Self::ContentTypeMismatch { declared, detected } => write!(
+                f,
+                "declared content type '{declared}' but contents are '{detected}'"
+            ),
+        }
+    }
+}
+
+/// An AttachmentIssue located within a Report
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttachmentProblem {
+    /// Name of the Test owning the attachment
+    pub test: String,
+    /// Name of the attachment, or `screenshot` for the test's screenshot
+    pub attachment: String,
+    /// Empty for a screenshot embedded in the report
+    pub path: PathBuf,
+    pub issue: AttachmentIssue,
+}
+
+/// Reads the first [`SNIFF_LEN`](content_type::SNIFF_LEN) bytes of a file
+fn read_head(path: &Path) -> io::Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(content_type::SNIFF_LEN);
+    File::open(path)?
+        .take(content_type::SNIFF_LEN as u64)
+        .read_to_end(&mut head)?;
+
+    Ok(head)
+}
+
+/// Decodes a data URI, base64 or percent-encoded, or returns `None` if `uri` is not one
+fn decode_data_uri(uri: &str) -> Option<io::Result<Vec<u8>>> {
+    let uri = uri.strip_prefix(DATA_URI_PREFIX)?;
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    Some(match uri.split_once(',') {
+        None => Err(invalid(String::from("data URI has no ',' before its data"))),
+        Some((media_type, data)) if media_type.ends_with(BASE64_PARAMETER) => {
+            STANDARD.decode(data).map_err(|e| invalid(e.to_string()))
+        }
+        Some((_, data)) => percent_decode(data).map_err(invalid),
+    })
+}
+
+/// Decodes the `%XX` escapes of the data of a non-base64 data URI
+fn percent_decode(data: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = data.bytes();
+    let mut decoded = Vec::with_capacity(data.len());
+
+    while let Some(byte) = bytes.next() {
+        if byte != b'%' {
+            decoded.push(byte);
+            continue;
+        }
+
+        let hex = [bytes.next(), bytes.next()];
+        let escape = match hex {
+            [Some(high), Some(low)] => std::str::from_utf8(&[high, low])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escape {
+            Some(byte) => decoded.push(byte),
+            None => return Err(String::from("data URI has an invalid '%' escape")),
+        }
+    }
+
+    Ok(decoded)
+}
+
 impl_extra!(Attachment);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_uris() {
+        let decode = |uri| decode_data_uri(uri).map(|data| data.map_err(|e| e.kind()));
+
+        assert_eq!(
+            decode("data:text/plain;base64,aGk="),
+            Some(Ok(b"hi".to_vec()))
+        );
+        assert_eq!(
+            decode("data:text/plain;charset=utf-8,a%20b%2C"),
+            Some(Ok(b"a b,".to_vec()))
+        );
+        assert_eq!(decode("data:,"), Some(Ok(vec![])));
+        assert_eq!(
+            decode("data:text/plain,100%"),
+            Some(Err(io::ErrorKind::InvalidData))
+        );
+        assert_eq!(
+            decode("data:text/plain"),
+            Some(Err(io::ErrorKind::InvalidData))
+        );
+        assert_eq!(decode("logs/data:1.txt"), None);
+    }
+}
@@ -0,0 +1,246 @@
+use std::path::Path;
+
+/// Fallback for contents that cannot be identified
+pub const OCTET_STREAM: &str = "application/octet-stream";
+
+/// Number of leading bytes [`sniff`] and [`detect`] need to see
+pub const SNIFF_LEN: usize = 512;
+
+/// Signatures as (offset, magic bytes, MIME type)
+const SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xff\xd8\xff", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"\x1f\x8b", "application/gzip"),
+    (0, b"\x28\xb5\x2f\xfd", "application/zstd"),
+    (0, b"\x1a\x45\xdf\xa3", "video/webm"),
+    (4, b"ftyp", "video/mp4"),
+    (257, b"ustar", "application/x-tar"),
+];
+
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("bmp", "image/bmp"),
+    ("svg", "image/svg+xml"),
+    ("pdf", "application/pdf"),
+    ("zip", "application/zip"),
+    ("gz", "application/gzip"),
+    ("zst", "application/zstd"),
+    ("tar", "application/x-tar"),
+    ("mp4", "video/mp4"),
+    ("m4a", "audio/mp4"),
+    ("mov", "video/quicktime"),
+    ("webm", "video/webm"),
+    ("json", "application/json"),
+    ("xml", "application/xml"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("csv", "text/csv"),
+    ("md", "text/markdown"),
+    ("yaml", "application/yaml"),
+    ("yml", "application/yaml"),
+    ("txt", "text/plain"),
+    ("log", "text/plain"),
+];
+
+/// Non-standard names of MIME types, with the standard name
+const ALIASES: &[(&str, &str)] = &[
+    ("image/jpg", "image/jpeg"),
+    ("image/pjpeg", "image/jpeg"),
+    ("image/x-png", "image/png"),
+    ("application/x-pdf", "application/pdf"),
+    ("application/x-zip-compressed", "application/zip"),
+    ("application/x-gzip", "application/gzip"),
+    ("application/x-zstd", "application/zstd"),
+    ("audio/x-m4a", "audio/mp4"),
+    ("audio/m4a", "audio/mp4"),
+    ("text/xml", "application/xml"),
+    ("text/yaml", "application/yaml"),
+    ("application/x-yaml", "application/yaml"),
+];
+
+/// Formats stored in a zip archive that are not named `+zip`
+const ZIP_BASED: &[&str] = &[
+    "application/java-archive",
+    "application/vnd.android.package-archive",
+    "application/vnd.openxmlformats-officedocument.",
+    "application/vnd.oasis.opendocument.",
+    "application/vnd.ms-xpsdocument",
+];
+
+/// Formats stored in an ISO base media file, whose `ftyp` box [`sniff`] reports as `video/mp4`
+const FTYP_BASED: &[&str] = &[
+    "video/quicktime",
+    "video/3gpp",
+    "video/3gpp2",
+    "video/x-m4v",
+    "audio/mp4",
+    "image/avif",
+    "image/heic",
+    "image/heif",
+];
+
+/// Identifies binary formats by their leading bytes.
+/// Text formats carry no reliable signature and yield `None`.
+pub fn sniff(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        return Some("image/webp");
+    }
+
+    SIGNATURES
+        .iter()
+        .find(|(offset, magic, _)| data.get(*offset..*offset + magic.len()) == Some(*magic))
+        .map(|(_, _, mime)| *mime)
+}
+
+/// Looks up the MIME type for the path's extension
+pub fn from_extension(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+
+    EXTENSIONS
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, mime)| *mime)
+}
+
+/// Determines a MIME type from contents first, then the extension, then whether it is text.
+/// `data` may be just the first [`SNIFF_LEN`] bytes.
+pub fn detect(data: &[u8], path: &Path) -> &'static str {
+    sniff(data)
+        .or_else(|| from_extension(path))
+        .unwrap_or_else(|| {
+            // A character cut off at the end of a prefix is not an error
+            let utf8 = match std::str::from_utf8(data) {
+                Ok(_) => true,
+                Err(e) => e.error_len().is_none(),
+            };
+            if !data.contains(&0) && utf8 {
+                "text/plain"
+            } else {
+                OCTET_STREAM
+            }
+        })
+}
+
+/// Lower-cases a MIME type, drops parameters such as `charset` and replaces aliases such as
+/// `image/jpg` with the standard name
+pub fn essence(content_type: &str) -> String {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    match ALIASES.iter().find(|(alias, _)| *alias == essence) {
+        Some((_, standard)) => String::from(*standard),
+        None => essence,
+    }
+}
+
+/// Compares MIME types by their [`essence`]
+pub fn same_essence(a: &str, b: &str) -> bool {
+    essence(a) == essence(b)
+}
+
+/// Checks whether contents [`sniff`]ed as `detected` can be of the `declared` type.
+/// Besides equal types, this accepts generic declared types and formats built on the detected
+/// container, such as `.docx` or `.jar` files sniffed as zip and QuickTime files sniffed as mp4.
+pub fn compatible(declared: &str, detected: &str) -> bool {
+    let declared = essence(declared);
+    let detected = essence(detected);
+    let prefixed = |types: &[&str]| types.iter().any(|t| declared.starts_with(t));
+
+    declared == detected
+        || declared == OCTET_STREAM
+        || match detected.as_str() {
+            "application/zip" => declared.ends_with("+zip") || prefixed(ZIP_BASED),
+            "video/mp4" => prefixed(FTYP_BASED),
+            _ => false,
+        }
+}
+
+/// Checks whether contents of the `declared` type carry a signature [`sniff`] recognizes, so
+/// that contents without one contradict the declaration
+pub fn has_signature(declared: &str) -> bool {
+    let declared = essence(declared);
+
+    declared != OCTET_STREAM
+        && (declared == "image/webp"
+            || SIGNATURES
+                .iter()
+                .any(|(_, _, mime)| compatible(&declared, mime)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniff_signatures() {
+        assert_eq!(sniff(b"\x89PNG\r\n\x1a\n...."), Some("image/png"));
+        assert_eq!(sniff(b"%PDF-1.7"), Some("application/pdf"));
+        assert_eq!(sniff(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff(b"\0\0\0\x18ftypmp42"), Some("video/mp4"));
+        assert_eq!(sniff(b"plain text"), None);
+        assert_eq!(sniff(b""), None);
+    }
+
+    #[test]
+    fn detect_with_fallbacks() {
+        assert_eq!(
+            detect(b"\xff\xd8\xff\xe0", Path::new("a.png")),
+            "image/jpeg"
+        );
+        assert_eq!(detect(b"{}", Path::new("a.JSON")), "application/json");
+        assert_eq!(detect(b"hello", Path::new("a")), "text/plain");
+        assert_eq!(detect(b"\0\x01\x02", Path::new("a")), OCTET_STREAM);
+    }
+
+    #[test]
+    fn compare_essence() {
+        assert!(same_essence("text/plain; charset=utf-8", "TEXT/PLAIN"));
+        assert!(!same_essence("image/png", "image/jpeg"));
+        assert!(same_essence("image/jpg", "image/jpeg"));
+        assert!(same_essence("application/x-gzip", "application/gzip"));
+    }
+
+    #[test]
+    fn compatible_containers() {
+        let docx = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+        assert!(compatible(docx, "application/zip"));
+        assert!(compatible("application/java-archive", "application/zip"));
+        assert!(compatible("application/epub+zip", "application/zip"));
+        assert!(compatible("video/quicktime", "video/mp4"));
+        assert!(compatible("audio/x-m4a", "video/mp4"));
+        assert!(compatible("application/octet-stream", "image/png"));
+        assert!(!compatible("application/zip", docx));
+        assert!(!compatible("application/pdf", "application/zip"));
+        assert!(!compatible("image/png", "video/mp4"));
+    }
+
+    #[test]
+    fn declared_signatures() {
+        assert!(has_signature("image/PNG"));
+        assert!(has_signature("image/jpg"));
+        assert!(has_signature("image/webp"));
+        assert!(has_signature("video/quicktime"));
+        assert!(!has_signature("text/plain"));
+        assert!(!has_signature("image/svg+xml"));
+        assert!(!has_signature(OCTET_STREAM));
+    }
+
+    #[test]
+    fn detect_text_prefix() {
+        // "é" cut after its first byte
+        assert_eq!(detect(b"caf\xc3", Path::new("a")), "text/plain");
+        assert_eq!(detect(b"\xc3caf", Path::new("a")), OCTET_STREAM);
+    }
+}
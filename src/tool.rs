@@ -7,7 +7,7 @@ use serde_json::Value;
 
 pub const TOOL_NAME: &str = "ctrf-rs";
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Tool {
     name: String,
@@ -28,3 +28,27 @@ impl Tool {
 }
 
 impl_extra!(Tool);
+
+/// Creates a [`Tool`](crate::tool::Tool) whose version is the calling crate's package version
+#[macro_export]
+macro_rules! tool {
+    ($name:expr) => {
+        $crate::tool::Tool::new($name, Some(String::from(env!("CARGO_PKG_VERSION"))))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_defaults_to_package_version() {
+        let tool = crate::tool!("runner");
+
+        assert_eq!(
+            tool,
+            Tool::new("runner", Some(String::from(env!("CARGO_PKG_VERSION"))))
+        );
+        assert_eq!(tool.extra, HashMap::new());
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{
+    environment::Environment,
+    error::Result,
+    extra::Extra,
+    report::{REPORT_FORMAT, SPEC_VERSION},
+    summary::Summary,
+    test::{Status, Test},
+    tool::Tool,
+};
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+    time::SystemTime,
+};
+
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Writes a CTRF report incrementally, so tests need not be held in memory.
+/// The header and tool are written on creation and each Test as it is added;
+/// the summary, environment and `extra` are written by [`ReportWriter::finish`].
+/// The output is equivalent to serializing the corresponding [`Report`](crate::report::Report),
+/// except that `results.tests` precedes `results.summary`.
+pub struct ReportWriter<W: Write> {
+    writer: W,
+    counts: HashMap<Status, usize>,
+    suites: HashSet<String>,
+    environment: Option<Environment>,
+    extra: HashMap<String, Value>,
+}
+
+impl<W: Write> ReportWriter<W> {
+    /// Starts a report, writing everything up to the opening of `results.tests`
+    pub fn new(
+        mut writer: W,
+        report_id: Option<Uuid>,
+        timestamp: Option<SystemTime>,
+        generated_by: Option<String>,
+        tool: &Tool,
+    ) -> Result<Self> {
+        write!(
+            writer,
+            r#"{{"reportFormat":{},"specVersion":{}"#,
+            serde_json::to_string(REPORT_FORMAT)?,
+            serde_json::to_string(&SPEC_VERSION)?
+        )?;
+        if let Some(id) = report_id {
+            write!(writer, r#","reportId":{}"#, serde_json::to_string(&id)?)?;
+        }
+        if let Some(ts) = timestamp {
+            let ts = format!("{ts:?}");
+            write!(writer, r#","timestamp":{}"#, serde_json::to_string(&ts)?)?;
+        }
+        if let Some(gen_by) = generated_by {
+            write!(
+                writer,
+                r#","generatedBy":{}"#,
+                serde_json::to_string(&gen_by)?
+            )?;
+        }
+        write!(
+            writer,
+            r#","results":{{"tool":{},"tests":["#,
+            serde_json::to_string(tool)?
+        )?;
+
+        Ok(Self {
+            writer,
+            counts: HashMap::new(),
+            suites: HashSet::new(),
+            environment: None,
+            extra: HashMap::new(),
+        })
+    }
+
+    /// Writes a Test and adds it to the summary counters.
+    /// A Test without an ID is written with its [`Test::stable_id`].
+    pub fn add_test(&mut self, test: &Test) -> Result<()> {
+        if self.tests() > 0 {
+            self.writer.write_all(b",")?;
+        }
+        if test.id.is_some() {
+            serde_json::to_writer(&mut self.writer, test)?;
+        } else {
+            let mut test = test.clone();
+            test.assign_id();
+            serde_json::to_writer(&mut self.writer, &test)?;
+        }
+
+        *self.counts.entry(test.status()).or_default() += 1;
+        if let Some(suite) = test.suite() {
+            if !self.suites.contains(suite) {
+                self.suites.insert(suite.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of Tests written so far
+    pub fn tests(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// Sets the Environment written on finish, can be None
+    pub fn environment(&mut self, environment: Option<Environment>) {
+        self.environment = environment;
+    }
+
+    /// Writes the summary, environment and `extra` to complete the document.
+    /// Returns the underlying writer, flushed.
+    pub fn finish(mut self, start: SystemTime, stop: SystemTime) -> Result<W> {
+        let mut summary = Summary::new(start, stop);
+        for (status, count) in &self.counts {
+            summary.set_count(*status, *count);
+        }
+        if !self.suites.is_empty() {
+            summary.suites(Some(self.suites.len()));
+        }
+
+        write!(
+            self.writer,
+            r#"],"summary":{}"#,
+            serde_json::to_string(&summary)?
+        )?;
+        if let Some(environment) = &self.environment {
+            write!(
+                self.writer,
+                r#","environment":{}"#,
+                serde_json::to_string(environment)?
+            )?;
+        }
+        if !self.extra.is_empty() {
+            write!(
+                self.writer,
+                r#","extra":{}"#,
+                serde_json::to_string(&self.extra)?
+            )?;
+        }
+        self.writer.write_all(b"}}")?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> Extra for ReportWriter<W> {
+    /// Inserts an element into the `results.extra` map.
+    /// Returns the value that it replaced, if one was present, or None if not.
+    fn insert_extra(&mut self, key: String, value: Value) -> Option<Value> {
+        self.extra.insert(key, value)
+    }
+
+    /// Removes the value at the provided key.
+    /// Returns the value if one was present, or None if not.
+    fn remove_extra(&mut self, key: &str) -> Option<Value> {
+        self.extra.remove(key)
+    }
+
+    /// Borrows the Extra map.
+    fn extra_map(&self) -> &HashMap<String, Value> {
+        &self.extra
+    }
+
+    /// Mutably borrows the Extra map.
+    fn extra_map_mut(&mut self) -> &mut HashMap<String, Value> {
+        &mut self.extra
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{report::Report, results::ResultsBuilder};
+
+    use std::time::Duration;
+
+    fn tests() -> Vec<Test> {
+        let mut tests = vec![];
+        for (t, status) in [Status::Passed, Status::Failed, Status::Skipped]
+            .iter()
+            .enumerate()
+        {
+            let mut test = Test::new(format!("test{t}"), *status, Duration::from_millis(5));
+            test.suite = Some(format!("suite{}", t % 2));
+            tests.push(test);
+        }
+        tests
+    }
+
+    #[test]
+    fn stream_matches_report() -> Result<()> {
+        let tool = Tool::new("ctrf-rs", None);
+        let id = Uuid::new_v4();
+        let time = SystemTime::now();
+        let mut environment = Environment::new();
+        environment.branch_name = Some(String::from("main"));
+
+        let mut writer = ReportWriter::new(vec![], Some(id), None, None, &tool)?;
+        for test in tests() {
+            writer.add_test(&test)?;
+        }
+        writer.environment(Some(environment.clone()));
+        writer.set_extra("seed", &42)?;
+        assert_eq!(writer.tests(), 3);
+        let output = writer.finish(time, time)?;
+
+        let mut builder = ResultsBuilder::new(tool);
+        for test in tests() {
+            builder.add_test(test);
+        }
+        builder.environment(Some(environment));
+        builder.set_extra("seed", &42)?;
+        let expected = Report::new(Some(id), None, None, builder.build(time, time));
+
+        let streamed = Report::from_slice(&output)?;
+        streamed.validate()?;
+        assert_eq!(streamed, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn stream_empty() -> Result<()> {
+        let time = SystemTime::now();
+        let tool = Tool::new("ctrf-rs", None);
+        let output =
+            ReportWriter::new(vec![], None, Some(time), None, &tool)?.finish(time, time)?;
+
+        let expected = Report::new(
+            None,
+            Some(time),
+            None,
+            ResultsBuilder::new(tool).build(time, time),
+        );
+        assert_eq!(Report::from_slice(&output)?, expected);
+
+        Ok(())
+    }
+}